@@ -0,0 +1,83 @@
+//! Compares the overhead of the different forms of recursive closures with a
+//! hand-written recursive function. Run with `cargo bench`.
+
+use fix_fn::{fix, fix_fn};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 20;
+
+/// Added to every leaf, so the compiler can't precompute the results. Closures capture
+/// it, functions that can't capture read it from here.
+static BASE: AtomicU64 = AtomicU64::new(0);
+
+fn bench(name: &str, f: impl Fn(u32) -> u64) {
+    let expected = fib(black_box(27));
+    assert_eq!(f(black_box(27)), expected, "{} computes the wrong result", name);
+
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        black_box(f(black_box(27)));
+        best = best.min(start.elapsed());
+    }
+    println!("{:<24} {:>10.3} ms", name, best.as_secs_f64() * 1000.0);
+}
+
+fn fib(i: u32) -> u64 {
+    if i <= 1 {
+        BASE.load(Ordering::Relaxed) + i as u64
+    } else {
+        fib(i - 1) + fib(i - 2)
+    }
+}
+
+fn main() {
+    BASE.store(black_box(0), Ordering::Relaxed);
+    let base = BASE.load(Ordering::Relaxed);
+
+    bench("fn", fib);
+
+    bench(
+        "fix_fn!",
+        fix_fn!(|fib, i: u32| -> u64 {
+            if i <= 1 {
+                base + i as u64
+            } else {
+                fib(i - 1) + fib(i - 2)
+            }
+        }),
+    );
+
+    bench(
+        "fix_fn!(static, ..)",
+        fix_fn!(static, |fib, i: u32| -> u64 {
+            if i <= 1 {
+                BASE.load(Ordering::Relaxed) + i as u64
+            } else {
+                fib(i - 1) + fib(i - 2)
+            }
+        }),
+    );
+
+    let fix_struct = fix_fn!(Fix, |fib, i: u32| -> u64 {
+        if i <= 1 {
+            base + i as u64
+        } else {
+            fib(i - 1) + fib(i - 2)
+        }
+    });
+    bench("fix_fn!(Fix, ..)", fix_struct.as_fn());
+
+    bench(
+        "fix(..)",
+        fix(|fib: &dyn Fn(u32) -> u64, i| {
+            if i <= 1 {
+                base + i as u64
+            } else {
+                fib(i - 1) + fib(i - 2)
+            }
+        }),
+    );
+}
@@ -0,0 +1,173 @@
+use alloc::boxed::Box;
+use core::future::Future;
+use core::pin::Pin;
+
+/// Future returned by the self handle of a [`fix_fn_async!`](crate::fix_fn_async!) closure.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Future returned by the self handle of a `fix_fn_async!(local, ..)` closure.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Like [`fix_fn!`](crate::fix_fn!), but creates a recursive async closure.
+///
+/// The body is run as an async closure, so it can `.await`, and calling the self handle
+/// returns a boxed future of the result. Calling the resulting closure also returns a
+/// future, so `f(n)` has to be awaited both inside and outside of the body. The boxing
+/// and pinning needed to make the future type finite happens internally.
+///
+/// The futures are [`Send`], so they can be spawned on multithreaded executors.
+/// This requires everything captured by the closure to be [`Sync`], and also [`Send`]
+/// if it is moved into the closure. Use `fix_fn_async!(local, |..| -> R { .. })` to create futures that
+/// are not [`Send`] without these requirements.
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_async;
+/// use std::collections::HashMap;
+///
+/// // stands in for e.g. an async directory listing
+/// async fn children(tree: &HashMap<u32, Vec<u32>>, node: u32) -> Vec<u32> {
+///     tree.get(&node).cloned().unwrap_or_default()
+/// }
+///
+/// let tree: HashMap<u32, Vec<u32>> = vec![(0, vec![1, 2]), (1, vec![3, 4]), (2, vec![5])]
+///     .into_iter()
+///     .collect();
+///
+/// let count = fix_fn_async!(|count, node: u32| -> usize {
+///     let mut total = 1;
+///     for child in children(&tree, node).await {
+///         total += count(child).await;
+///     }
+///     total
+/// });
+///
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = Box::pin(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     match future.as_mut().poll(&mut cx) {
+/// #         std::task::Poll::Ready(result) => result,
+/// #         std::task::Poll::Pending => unreachable!(),
+/// #     }
+/// # }
+/// # fn assert_send<T: Send>(_: &T) {}
+/// # assert_send(&count(0));
+/// assert_eq!(block_on(count(0)), 6);
+/// ```
+///
+/// Futures that are not [`Send`]:
+///
+/// ```
+/// use fix_fn::fix_fn_async;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let calls = Rc::new(Cell::new(0));
+/// let fib = fix_fn_async!(local, |fib, i: u32| -> u32 {
+///     calls.set(calls.get() + 1);
+///     if i <= 1 {
+///         i
+///     } else {
+///         fib(i - 1).await + fib(i - 2).await
+///     }
+/// });
+///
+/// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+/// #     let mut future = Box::pin(future);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     match future.as_mut().poll(&mut cx) {
+/// #         std::task::Poll::Ready(result) => result,
+/// #         std::task::Poll::Pending => unreachable!(),
+/// #     }
+/// # }
+/// assert_eq!(block_on(fib(10)), 55);
+/// assert_eq!(calls.get(), 177);
+/// ```
+#[macro_export]
+macro_rules! fix_fn_async {
+    (local, $($rest:tt)*) => {
+        $crate::__fix_fn_async!([] [$crate::__private::LocalBoxFuture] [$crate::__private::Rc] $($rest)*)
+    };
+    ($($rest:tt)*) => {
+        $crate::__fix_fn_async!([Send + Sync] [$crate::__private::BoxFuture] [$crate::__private::Arc] $($rest)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_async {
+    (
+        [$($sync:tt)*] [$($box_future:tt)*] [$($rc:tt)*]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn: $($sync)* {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $($box_future)*<'_, $ret_type>;
+        }
+
+        struct HideFnImpl<F, B>(F, B)
+        where
+            F: AsyncFn(&dyn HideFn, $($arg_type ,)*) -> $ret_type + $($sync)*,
+            B: for<'a> Fn(&'a F, &'a dyn HideFn, $($arg_type ,)*) -> $($box_future)*<'a, $ret_type> + $($sync)*;
+
+        impl<F, B> HideFn for HideFnImpl<F, B>
+        where
+            F: AsyncFn(&dyn HideFn, $($arg_type ,)*) -> $ret_type + $($sync)*,
+            B: for<'a> Fn(&'a F, &'a dyn HideFn, $($arg_type ,)*) -> $($box_future)*<'a, $ret_type> + $($sync)*,
+        {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $($box_future)*<'_, $ret_type> {
+                (self.1)(&self.0, self, $($arg_name ,)*)
+            }
+        }
+
+        let inner = $($rc)*::new(HideFnImpl(
+            async $($mov)? |$self_arg: &dyn HideFn, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            },
+            // boxes the future where the type of the body is known,
+            // so the future only has to be `Send` if it is requested to be
+            |body, self_arg, $($arg_name ,)*| $crate::__private::Box::pin(body(self_arg, $($arg_name ,)*)),
+        ));
+
+        move |$($arg_name : $arg_type),*| {
+            let inner = ::core::clone::Clone::clone(&inner);
+            async move { inner.call($($arg_name),*).await }
+        }
+    }};
+    (
+        [$($sync:tt)*] [$($box_future:tt)*] [$($rc:tt)*]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_async needs return type!");
+    };
+    (
+        [$($sync:tt)*] [$($box_future:tt)*] [$($rc:tt)*]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$($sync:tt)*] [$($box_future:tt)*] [$($rc:tt)*]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$($sync:tt)*] [$($box_future:tt)*] [$($rc:tt)*] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_async] [[$($sync)*] [$($box_future)*] [$($rc)*]] $($mov)? |$self_arg, $($rest)*)
+    };
+}
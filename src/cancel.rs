@@ -0,0 +1,148 @@
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::guard::Guard;
+
+/// Error returned by a `fix_fn!(cancel = .., ..)` closure if the recursion was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("recursion was cancelled")
+    }
+}
+
+impl core::error::Error for Cancelled {}
+
+/// Error returned by a `fix_fn!(deadline = .., ..)` closure if the recursion ran past
+/// its deadline.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeadlineExceeded;
+
+#[cfg(feature = "std")]
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("recursion exceeded its deadline")
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for DeadlineExceeded {}
+
+/// Error returned by a `fix_fn!(cancel = .., deadline = .., ..)` closure.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interrupted {
+    /// The recursion was cancelled.
+    Cancelled,
+    /// The recursion ran past its deadline.
+    DeadlineExceeded,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interrupted::Cancelled => Cancelled.fmt(f),
+            Interrupted::DeadlineExceeded => DeadlineExceeded.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for Interrupted {}
+
+#[cfg(feature = "std")]
+impl From<Cancelled> for Interrupted {
+    fn from(_: Cancelled) -> Self {
+        Interrupted::Cancelled
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<DeadlineExceeded> for Interrupted {
+    fn from(_: DeadlineExceeded) -> Self {
+        Interrupted::DeadlineExceeded
+    }
+}
+
+/// [`Guard`] behind `fix_fn!(cancel = .., ..)`.
+pub struct Cancellation<'a>(pub &'a AtomicBool);
+
+impl Guard for Cancellation<'_> {
+    type Error = Cancelled;
+
+    #[inline]
+    fn enter(&self) -> Result<(), Cancelled> {
+        if self.0.load(Ordering::Relaxed) {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// [`Guard`] behind `fix_fn!(deadline = .., ..)`.
+#[cfg(feature = "std")]
+pub struct Deadline(pub Instant);
+
+#[cfg(feature = "std")]
+impl Guard for Deadline {
+    type Error = DeadlineExceeded;
+
+    #[inline]
+    fn enter(&self) -> Result<(), DeadlineExceeded> {
+        if Instant::now() >= self.0 {
+            Err(DeadlineExceeded)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// [`Guard`] behind `fix_fn!(cancel = .., deadline = .., ..)`.
+#[cfg(feature = "std")]
+pub struct CancellationOrDeadline<'a>(pub &'a AtomicBool, pub Instant);
+
+#[cfg(feature = "std")]
+impl Guard for CancellationOrDeadline<'_> {
+    type Error = Interrupted;
+
+    #[inline]
+    fn enter(&self) -> Result<(), Interrupted> {
+        Cancellation(self.0).enter()?;
+        Deadline(self.1).enter()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_deadline {
+    ([$deadline:expr] [] $($rest:tt)*) => {
+        $crate::__fix_fn_guarded!(
+            [$crate::__private::Deadline($deadline)] [$crate::DeadlineExceeded]
+            $($rest)*
+        )
+    };
+    ([$deadline:expr] [$cancel:expr] $($rest:tt)*) => {
+        $crate::__fix_fn_guarded!(
+            [$crate::__private::CancellationOrDeadline($cancel, $deadline)] [$crate::Interrupted]
+            $($rest)*
+        )
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_deadline {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn!(deadline = .., ..) requires the `std` feature of fix_fn!");
+    };
+}
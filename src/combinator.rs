@@ -0,0 +1,79 @@
+macro_rules! fix_functions {
+    ($(
+        $(#[$attr:meta])*
+        fn $name:ident($($arg_name:ident : $arg_type:ident),*);
+    )*) => {$(
+        $(#[$attr])*
+        #[inline]
+        pub fn $name<$($arg_type ,)* R, F>(f: F) -> impl Fn($($arg_type),*) -> R
+        where
+            F: Fn(&dyn Fn($($arg_type),*) -> R, $($arg_type),*) -> R,
+        {
+            #[inline]
+            fn call<$($arg_type ,)* R, F>(f: &F, $($arg_name : $arg_type),*) -> R
+            where
+                F: Fn(&dyn Fn($($arg_type),*) -> R, $($arg_type),*) -> R,
+            {
+                f(&|$($arg_name),*| call(f, $($arg_name),*), $($arg_name),*)
+            }
+
+            move |$($arg_name),*| call(&f, $($arg_name),*)
+        }
+    )*};
+}
+
+fix_functions! {
+    /// Function version of [`fix_fn!`](crate::fix_fn!) for closures with one parameter.
+    ///
+    /// Takes a closure whose first parameter is a `&dyn Fn(A) -> R` to the closure itself
+    /// and returns a recursive closure with the first parameter eliminated. Unlike the
+    /// macro, this works with any existing closure or function of the right type and
+    /// the types of the closure do not have to be annotated if they can be inferred.
+    ///
+    /// [`fix2`] to [`fix6`] do the same for closures with more parameters.
+    /// [`Fix::new`](crate::Fix::new) takes all parameters as a tuple instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fix_fn::fix;
+    ///
+    /// let fib = fix(|fib: &dyn Fn(u32) -> u32, i| {
+    ///     if i <= 1 {
+    ///         i
+    ///     } else {
+    ///         fib(i - 1) + fib(i - 2)
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(fib(7), 13);
+    ///
+    /// // any function with the right signature works
+    /// fn factorial_step(factorial: &dyn Fn(u64) -> u64, n: u64) -> u64 {
+    ///     if n == 0 { 1 } else { n * factorial(n - 1) }
+    /// }
+    /// let factorial = fix(factorial_step);
+    /// assert_eq!(factorial(10), 3_628_800);
+    /// ```
+    fn fix(a: A);
+    /// Like [`fix`], but for closures with two parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fix_fn::fix2;
+    ///
+    /// let gcd = fix2(|gcd: &dyn Fn(u32, u32) -> u32, a, b| if b == 0 { a } else { gcd(b, a % b) });
+    ///
+    /// assert_eq!(gcd(48, 18), 6);
+    /// ```
+    fn fix2(a: A, b: B);
+    /// Like [`fix`], but for closures with three parameters.
+    fn fix3(a: A, b: B, c: C);
+    /// Like [`fix`], but for closures with four parameters.
+    fn fix4(a: A, b: B, c: C, d: D);
+    /// Like [`fix`], but for closures with five parameters.
+    fn fix5(a: A, b: B, c: C, d: D, e: E);
+    /// Like [`fix`], but for closures with six parameters.
+    fn fix6(a: A, b: B, c: C, d: D, e: E, g: G);
+}
@@ -0,0 +1,220 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+
+/// Error returned by a `fix_fn!(cycle, ..)` closure if a call reenters a call with equal
+/// arguments that is still running.
+///
+/// `Args` is the type of the only parameter of the closure except the self handle, or
+/// a tuple of all of them if there are several.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cycle<Args> {
+    /// Arguments of the calls that form the cycle, from the outermost call that was
+    /// reentered to the call that reentered it. The first and the last element are equal.
+    pub path: Vec<Args>,
+}
+
+impl<Args: fmt::Debug> fmt::Display for Cycle<Args> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("recursion cycle: ")?;
+        for (i, args) in self.path.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "{:?}", args)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Args: fmt::Debug> core::error::Error for Cycle<Args> {}
+
+/// Arguments of the calls that are currently running, as used by `fix_fn!(cycle, ..)`.
+pub struct CycleTracker<Args> {
+    running: RefCell<Vec<Args>>,
+}
+
+impl<Args: PartialEq + Clone> CycleTracker<Args> {
+    #[inline]
+    pub fn new() -> Self {
+        CycleTracker {
+            running: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Registers a call with `args` until the result is dropped, or returns the cycle if
+    /// a call with equal arguments is already running.
+    #[inline]
+    pub fn enter(&self, args: Args) -> Result<Running<'_, Args>, Cycle<Args>> {
+        let mut running = self.running.borrow_mut();
+        if let Some(start) = running.iter().position(|running| *running == args) {
+            let mut path = running[start..].to_vec();
+            path.push(args);
+            return Err(Cycle { path });
+        }
+        running.push(args);
+        Ok(Running(self))
+    }
+}
+
+impl<Args: PartialEq + Clone> Default for CycleTracker<Args> {
+    fn default() -> Self {
+        CycleTracker::new()
+    }
+}
+
+/// A call registered by [`CycleTracker::enter`].
+pub struct Running<'a, Args>(&'a CycleTracker<Args>);
+
+impl<Args> Drop for Running<'_, Args> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.running.borrow_mut().pop();
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_cycle {
+    (@args_type $arg_type:ty) => {
+        $arg_type
+    };
+    (@args_type $($arg_type:ty),*) => {
+        ($($arg_type ,)*)
+    };
+    (@args $arg_name:ident) => {
+        ::core::clone::Clone::clone(&$arg_name)
+    };
+    (@args $($arg_name:ident),*) => {
+        ($(::core::clone::Clone::clone(&$arg_name) ,)*)
+    };
+
+    (
+        []
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        type Args = $crate::__fix_fn_cycle!(@args_type $($arg_type),*);
+
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> ::core::result::Result<$ret_type, $crate::Cycle<Args>>;
+        }
+
+        struct HideFnImpl<F>($crate::__private::CycleTracker<Args>, F)
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> ::core::result::Result<$ret_type, $crate::Cycle<Args>>;
+
+        impl<F> HideFn for HideFnImpl<F>
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> ::core::result::Result<$ret_type, $crate::Cycle<Args>>,
+        {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> ::core::result::Result<$ret_type, $crate::Cycle<Args>> {
+                let _running = self.0.enter($crate::__fix_fn_cycle!(@args $($arg_name),*))?;
+                self.1(self, $($arg_name ,)*)
+            }
+        }
+
+        let inner = HideFnImpl(
+            $crate::__private::CycleTracker::new(),
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> ::core::result::Result<$ret_type, $crate::Cycle<Args>> {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                ::core::result::Result::Ok({
+                    $body
+                })
+            }
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> ::core::result::Result<$ret_type, $crate::Cycle<Args>> {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        [$fallback:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        type Args = $crate::__fix_fn_cycle!(@args_type $($arg_type),*);
+
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        // gives the fallback closure its signature
+        struct Fallback<G: Fn($($arg_type ,)*) -> $ret_type>(G);
+
+        struct HideFnImpl<F, G>($crate::__private::CycleTracker<Args>, Fallback<G>, F)
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type,
+            G: Fn($($arg_type ,)*) -> $ret_type;
+
+        impl<F, G> HideFn for HideFnImpl<F, G>
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type,
+            G: Fn($($arg_type ,)*) -> $ret_type,
+        {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                let _running = match self.0.enter($crate::__fix_fn_cycle!(@args $($arg_name),*)) {
+                    ::core::result::Result::Ok(running) => running,
+                    ::core::result::Result::Err(_) => return (self.1 .0)($($arg_name),*),
+                };
+                self.2(self, $($arg_name ,)*)
+            }
+        }
+
+        let inner = HideFnImpl(
+            $crate::__private::CycleTracker::new(),
+            Fallback($fallback),
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            }
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        [$($fallback:expr)?]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        [$($fallback:expr)?]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$($fallback:expr)?]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$($fallback:expr)?] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_cycle] [[$($fallback)?]] $($mov)? |$self_arg, $($rest)*)
+    };
+    ([$($fallback:expr)?] $($rest:tt)*) => {
+        compile_error!("fix_fn!(cycle, ..) can't be combined with other modes and expects a closure of the form `|f, arg: Type, ..| -> R { .. }`!");
+    };
+}
@@ -0,0 +1,64 @@
+use core::cell::Cell;
+use core::fmt;
+
+use crate::guard::Guard;
+
+/// Error returned by a `fix_fn!(max_depth = .., ..)` closure if the recursion nests
+/// deeper than allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DepthExceeded {
+    /// Depth of the call that was refused. The top-level call has depth 1.
+    pub depth: usize,
+    /// The maximal depth that was allowed.
+    pub limit: usize,
+}
+
+impl fmt::Display for DepthExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "recursion depth {} exceeds the limit of {}",
+            self.depth, self.limit
+        )
+    }
+}
+
+impl core::error::Error for DepthExceeded {}
+
+/// [`Guard`] behind `fix_fn!(max_depth = .., ..)`.
+pub struct DepthLimit {
+    depth: Cell<usize>,
+    limit: usize,
+}
+
+impl DepthLimit {
+    #[inline]
+    pub fn new(limit: usize) -> Self {
+        DepthLimit {
+            depth: Cell::new(0),
+            limit,
+        }
+    }
+}
+
+impl Guard for DepthLimit {
+    type Error = DepthExceeded;
+
+    #[inline]
+    fn enter(&self) -> Result<(), DepthExceeded> {
+        let depth = self.depth.get() + 1;
+        if depth > self.limit {
+            return Err(DepthExceeded {
+                depth,
+                limit: self.limit,
+            });
+        }
+        self.depth.set(depth);
+        Ok(())
+    }
+
+    #[inline]
+    fn exit(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
@@ -0,0 +1,174 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A recursive closure with a nameable type, as created by `fix_fn!(Fix, ..)`.
+///
+/// `F` is the closure that was passed to the macro. It takes a self handle and all
+/// other parameters as a tuple of type `Args` and returns `R`. The self handle is a
+/// `&dyn Fn(Args) -> R` that calls the recursive closure again.
+///
+/// Unlike the closure returned by [`fix_fn!`](crate::fix_fn!), a `Fix` can be named in
+/// struct fields and return types by using a generic parameter or `impl Fn` for `F`.
+/// It is called with [`Fix::call`] or [`Fix::call_tuple`] and can be turned into a
+/// closure with [`Fix::as_fn`] or [`Fix::into_fn`].
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::{fix_fn, Fix};
+///
+/// struct Evaluator<F> {
+///     fib: Fix<F, (u32,), u64>,
+/// }
+///
+/// fn evaluator() -> Evaluator<impl Fn(&dyn Fn((u32,)) -> u64, (u32,)) -> u64> {
+///     Evaluator {
+///         fib: fix_fn!(Fix, |fib, i: u32| -> u64 {
+///             if i <= 1 {
+///                 i as u64
+///             } else {
+///                 fib(i - 1) + fib(i - 2)
+///             }
+///         }),
+///     }
+/// }
+///
+/// let evaluator = evaluator();
+/// assert_eq!(evaluator.fib.call(10), 55);
+/// assert_eq!(evaluator.fib.call_tuple((11,)), 89);
+/// assert_eq!((1..=5).map(evaluator.fib.as_fn()).collect::<Vec<_>>(), [1, 1, 2, 3, 5]);
+/// ```
+pub struct Fix<F, Args, R> {
+    f: F,
+    marker: PhantomData<fn(Args) -> R>,
+}
+
+impl<F, Args, R> Fix<F, Args, R>
+where
+    F: Fn(&dyn Fn(Args) -> R, Args) -> R,
+{
+    /// Creates a recursive closure from `f`, which gets a handle to the result as first
+    /// argument.
+    #[inline]
+    pub fn new(f: F) -> Self {
+        Fix {
+            f,
+            marker: PhantomData,
+        }
+    }
+
+    /// Calls the closure with a tuple of all arguments.
+    #[inline]
+    pub fn call_tuple(&self, args: Args) -> R {
+        (self.f)(&|args| self.call_tuple(args), args)
+    }
+
+    /// Returns the closure that was passed to [`Fix::new`].
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F: Clone, Args, R> Clone for Fix<F, Args, R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Fix {
+            f: self.f.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<F, Args, R> fmt::Debug for Fix<F, Args, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fix").finish_non_exhaustive()
+    }
+}
+
+macro_rules! impl_fix_call {
+    ($(($($arg_name:ident : $arg_type:ident),*))*) => {$(
+        impl<F, $($arg_type ,)* R> Fix<F, ($($arg_type ,)*), R>
+        where
+            F: Fn(&dyn Fn(($($arg_type ,)*)) -> R, ($($arg_type ,)*)) -> R,
+        {
+            /// Calls the closure.
+            #[inline]
+            pub fn call(&self, $($arg_name : $arg_type),*) -> R {
+                self.call_tuple(($($arg_name ,)*))
+            }
+
+            /// Borrows the recursive closure as a normal closure.
+            #[inline]
+            pub fn as_fn(&self) -> impl Fn($($arg_type),*) -> R + '_ {
+                move |$($arg_name),*| self.call_tuple(($($arg_name ,)*))
+            }
+
+            /// Turns the recursive closure into a normal closure.
+            #[inline]
+            pub fn into_fn(self) -> impl Fn($($arg_type),*) -> R {
+                move |$($arg_name),*| self.call_tuple(($($arg_name ,)*))
+            }
+        }
+    )*};
+}
+
+impl_fix_call! {
+    ()
+    (a: A)
+    (a: A, b: B)
+    (a: A, b: B, c: C)
+    (a: A, b: B, c: C, d: D)
+    (a: A, b: B, c: C, d: D, e: E)
+    (a: A, b: B, c: C, d: D, e: E, g: G)
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_fix {
+    (
+        [$($constructor:tt)*]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        $($constructor)*::new(
+            #[inline]
+            $($mov)?
+            |$self_arg: &dyn Fn(($($arg_type ,)*)) -> $ret_type, ($($arg_name ,)*): ($($arg_type ,)*)| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg(($($arg_name ,)*));
+                {
+                    $body
+                }
+            }
+        )
+    };
+    (
+        [$($constructor:tt)*]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        [$($constructor:tt)*]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$($constructor:tt)*]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$($constructor:tt)*] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_fix] [[$($constructor)*]] $($mov)? |$self_arg, $($rest)*)
+    };
+}
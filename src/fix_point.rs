@@ -0,0 +1,182 @@
+/// Decides whether a fixpoint iteration has converged, see [`FixPoint::until`].
+///
+/// Implemented by [`Equal`] and by all closures that take the previous and the next
+/// value.
+pub trait Convergence<T> {
+    /// Returns whether the iteration stops after it produced `next` from `previous`.
+    fn converged(&mut self, previous: &T, next: &T) -> bool;
+}
+
+impl<T, P> Convergence<T> for P
+where
+    P: FnMut(&T, &T) -> bool,
+{
+    #[inline]
+    fn converged(&mut self, previous: &T, next: &T) -> bool {
+        self(previous, next)
+    }
+}
+
+/// The default [`Convergence`] of [`fix_point`]: stops as soon as a step returns a value
+/// equal to its input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Equal;
+
+impl<T: PartialEq> Convergence<T> for Equal {
+    #[inline]
+    fn converged(&mut self, previous: &T, next: &T) -> bool {
+        previous == next
+    }
+}
+
+/// The outcome of [`FixPoint::run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Iterated<T> {
+    /// The last value the step returned, or the initial value if the step never ran.
+    pub value: T,
+    /// How often the step ran.
+    pub iterations: usize,
+    /// Whether the iteration converged. `false` if it stopped at the iteration cap.
+    pub converged: bool,
+}
+
+/// Iterates `x = step(x)` starting from `init`, see [`FixPoint`].
+///
+/// By default, the iteration stops when `step` returns a value equal to its input and
+/// runs without a cap.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_point;
+/// use std::collections::BTreeSet;
+///
+/// let edges = [(1, 2), (2, 3), (3, 1), (3, 4)];
+///
+/// // transitive closure
+/// let closure = fix_point(BTreeSet::from(edges), |paths| {
+///     let mut next = paths.clone();
+///     for &(a, b) in paths {
+///         for &(c, d) in paths {
+///             if b == c {
+///                 next.insert((a, d));
+///             }
+///         }
+///     }
+///     next
+/// })
+/// .run();
+///
+/// assert!(closure.converged);
+/// assert_eq!(closure.value.len(), 12);
+/// assert!(closure.value.contains(&(2, 2)));
+/// assert!(!closure.value.contains(&(4, 1)));
+/// ```
+///
+/// Numeric iterations rarely reach an exact fixpoint, so they need a custom convergence
+/// criterion and possibly an iteration cap:
+///
+/// ```
+/// use fix_fn::fix_point;
+///
+/// // Babylonian method for the square root of 2
+/// let sqrt = fix_point(1.0_f64, |x| (x + 2.0 / x) / 2.0)
+///     .until(|previous: &f64, next: &f64| (previous - next).abs() < 1e-12)
+///     .max_iterations(100)
+///     .run();
+///
+/// assert!(sqrt.converged);
+/// assert!(sqrt.iterations < 10);
+/// assert!((sqrt.value - 2.0_f64.sqrt()).abs() < 1e-12);
+///
+/// // this one oscillates and never converges
+/// let flip = fix_point(1, |x| -x).max_iterations(5).run();
+/// assert_eq!((flip.value, flip.iterations, flip.converged), (-1, 5, false));
+/// ```
+#[inline]
+pub fn fix_point<T, F>(init: T, step: F) -> FixPoint<T, F>
+where
+    F: FnMut(&T) -> T,
+{
+    FixPoint {
+        init,
+        step,
+        until: Equal,
+        max_iterations: None,
+    }
+}
+
+/// A fixpoint iteration of values, as created by [`fix_point`].
+///
+/// Unlike [`fix_fn!`](crate::fix_fn!), which ties a function to itself, this applies
+/// the step function to a value over and over until the value settles.
+#[derive(Debug, Clone)]
+#[must_use = "the iteration only runs when calling `run`"]
+pub struct FixPoint<T, F, P = Equal> {
+    init: T,
+    step: F,
+    until: P,
+    max_iterations: Option<usize>,
+}
+
+impl<T, F, P> FixPoint<T, F, P>
+where
+    F: FnMut(&T) -> T,
+{
+    /// Replaces the convergence criterion. The iteration stops as soon as `until`
+    /// returns `true` for the input and the output of a step.
+    #[inline]
+    pub fn until<Q>(self, until: Q) -> FixPoint<T, F, Q>
+    where
+        Q: Convergence<T>,
+    {
+        FixPoint {
+            init: self.init,
+            step: self.step,
+            until,
+            max_iterations: self.max_iterations,
+        }
+    }
+
+    /// Stops the iteration after the step ran `max_iterations` times, even if it has
+    /// not converged.
+    #[inline]
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    /// Runs the iteration.
+    pub fn run(self) -> Iterated<T>
+    where
+        P: Convergence<T>,
+    {
+        let FixPoint {
+            init: mut value,
+            mut step,
+            mut until,
+            max_iterations,
+        } = self;
+        let mut iterations = 0;
+        loop {
+            if max_iterations == Some(iterations) {
+                return Iterated {
+                    value,
+                    iterations,
+                    converged: false,
+                };
+            }
+            let next = step(&value);
+            iterations += 1;
+            let converged = until.converged(&value, &next);
+            value = next;
+            if converged {
+                return Iterated {
+                    value,
+                    iterations,
+                    converged: true,
+                };
+            }
+        }
+    }
+}
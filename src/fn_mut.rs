@@ -0,0 +1,114 @@
+/// Like [`fix_fn!`](crate::fix_fn!), but produces an [`FnMut`] closure that owns
+/// mutable state across its recursive calls.
+///
+/// A closure cannot call itself while it is running and still hold exclusive access
+/// to its own captures, so the mutable state is not captured. Instead it is passed to
+/// the macro as the first argument, followed by the closure definition. The state is
+/// moved into the resulting closure; pass `&mut value` to keep ownership.
+///
+/// The first parameter of the closure is the self handle. It dereferences to the state
+/// and recursive calls go through its `call` method, so the body can mutate the
+/// state between and around recursive calls.
+///
+/// The closure itself only gets shared access to the rest of its environment.
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_mut;
+///
+/// let mut visited = Vec::new();
+/// let mut walk = fix_fn_mut!(&mut visited, |walk, n: u32| -> u32 {
+///     walk.push(n);
+///     if n == 0 {
+///         0
+///     } else {
+///         // walk.call recurses while walk still derefs to the state
+///         let below = walk.call(n - 1);
+///         walk.push(n);
+///         below + 1
+///     }
+/// });
+///
+/// assert_eq!(walk(2), 2);
+/// assert_eq!(visited, [2, 1, 0, 1, 2]);
+/// ```
+#[macro_export]
+macro_rules! fix_fn_mut {
+    (
+        $state:expr, $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        struct FixMut<'a, S> {
+            state: &'a mut S,
+            body: &'a dyn Fn(&mut FixMut<'_, S>, $($arg_type ,)*) -> $ret_type,
+        }
+
+        impl<S> FixMut<'_, S> {
+            #[inline]
+            fn call(&mut self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                let body = self.body;
+                body(self, $($arg_name ,)*)
+            }
+        }
+
+        impl<S> ::core::ops::Deref for FixMut<'_, S> {
+            type Target = S;
+
+            #[inline]
+            fn deref(&self) -> &S {
+                self.state
+            }
+        }
+
+        impl<S> ::core::ops::DerefMut for FixMut<'_, S> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut S {
+                self.state
+            }
+        }
+
+        struct HideFnImpl<S, F: Fn(&mut FixMut<'_, S>, $($arg_type ,)*) -> $ret_type>(S, F);
+
+        let HideFnImpl(mut state, inner) = HideFnImpl(
+            $state,
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                $body
+            }
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            FixMut { state: &mut state, body: &inner }.call($($arg_name),*)
+        }
+    }};
+    (
+        $state:expr, $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_mut needs return type!");
+    };
+    (
+        $state:expr, $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $state:expr, $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $state:expr, $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn_mut] [$state,] $($mov)? |$self_arg, $($rest)*)
+    };
+}
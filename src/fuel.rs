@@ -0,0 +1,77 @@
+use core::cell::Cell;
+use core::fmt;
+
+use crate::guard::Guard;
+
+/// Error returned by a `fix_fn!(fuel = .., ..)` closure if the fuel is used up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutOfFuel {
+    /// Number of calls that were made before the fuel ran out.
+    pub calls: usize,
+}
+
+impl fmt::Display for OutOfFuel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ran out of fuel after {} calls", self.calls)
+    }
+}
+
+impl core::error::Error for OutOfFuel {}
+
+/// Budget for the total number of calls of a `fix_fn!(fuel = .., ..)` closure.
+///
+/// Every call, including the top-level call, consumes one unit of fuel. Pass the fuel
+/// by reference to query it inside the body or after the recursion, or to share it
+/// between several closures.
+#[derive(Debug, Clone, Default)]
+pub struct Fuel {
+    remaining: Cell<usize>,
+    consumed: Cell<usize>,
+}
+
+impl Fuel {
+    /// Creates fuel for `calls` calls.
+    #[inline]
+    pub fn new(calls: usize) -> Self {
+        Fuel {
+            remaining: Cell::new(calls),
+            consumed: Cell::new(0),
+        }
+    }
+
+    /// Number of calls that can still be made.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining.get()
+    }
+
+    /// Number of calls that were made so far.
+    #[inline]
+    pub fn consumed(&self) -> usize {
+        self.consumed.get()
+    }
+
+    /// Adds fuel for `calls` more calls.
+    #[inline]
+    pub fn refill(&self, calls: usize) {
+        self.remaining.set(self.remaining.get().saturating_add(calls));
+    }
+}
+
+impl Guard for Fuel {
+    type Error = OutOfFuel;
+
+    #[inline]
+    fn enter(&self) -> Result<(), OutOfFuel> {
+        match self.remaining.get().checked_sub(1) {
+            Some(remaining) => {
+                self.remaining.set(remaining);
+                self.consumed.set(self.consumed.get() + 1);
+                Ok(())
+            }
+            None => Err(OutOfFuel {
+                calls: self.consumed.get(),
+            }),
+        }
+    }
+}
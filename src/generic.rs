@@ -0,0 +1,116 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_generic {
+    // leading lifetime parameters belong to the resulting value
+    (@lifetimes [$($lt:lifetime)*] $next:lifetime , $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@lifetimes [$($lt)* $next] $($rest)*)
+    };
+    (@lifetimes [$($lt:lifetime)*] $next:lifetime > $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@closure [$($lt)* $next] [] $($rest)*)
+    };
+    (@lifetimes [$($lt:lifetime)*] $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics [$($lt)*] [] [] $($rest)*)
+    };
+
+    // the remaining parameters belong to the `call` method,
+    // the third list counts the currently open `<` to find the closing `>`
+    (@generics $lt:tt [$($gen:tt)*] [] > $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@closure $lt [$($gen)*] $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [< $($open:tt)*] > $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics $lt [$($gen)* >] [$($open)*] $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [<] >> $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics $lt [$($gen)* >] [] > $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [< < $($open:tt)*] >> $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics $lt [$($gen)* >>] [$($open)*] $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [$($open:tt)*] < $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics $lt [$($gen)* <] [< $($open)*] $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [$($open:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@generics $lt [$($gen)* $next] [$($open)*] $($rest)*)
+    };
+    (@generics $lt:tt [$($gen:tt)*] [$($open:tt)*]) => {
+        compile_error!("Missing `>` after the generic parameters passed to fix_fn!");
+    };
+
+    (@capture $cap:ident) => {
+        $cap
+    };
+    (@capture $cap:ident $init:expr) => {
+        $init
+    };
+
+    (
+        @closure [$($lt:lifetime)*] [$($gen:tt)*]
+        $([$($cap:ident : $cap_type:ty $(= $init:expr)?),* $(,)?])?
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        struct FixFnGeneric<$($lt),*> {
+            $($($cap: $cap_type ,)*)?
+            lifetimes: ::core::marker::PhantomData<($(&$lt () ,)*)>,
+        }
+
+        impl<$($lt),*> FixFnGeneric<$($lt),*> {
+            #[inline]
+            fn call<$($gen)*>(&self, $($arg_name : $arg_type),*) -> $ret_type {
+                let $self_arg = self;
+                $($(
+                    #[allow(unused_variables)]
+                    let $cap = &self.$cap;
+                )*)?
+                {
+                    $body
+                }
+            }
+        }
+
+        FixFnGeneric {
+            $($($cap: $crate::__fix_fn_generic!(@capture $cap $($init)?) ,)*)?
+            lifetimes: ::core::marker::PhantomData,
+        }
+    }};
+    (
+        @closure $lt:tt $gen:tt
+        $([$($captures:tt)*])?
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        @closure $lt:tt $gen:tt
+        $([$($captures:tt)*])?
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        @closure $lt:tt $gen:tt
+        $([$($captures:tt)*])?
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        @closure $lt:tt $gen:tt
+        $([$($captures:tt)*])?
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!(
+            [$crate::__fix_fn_generic] [@closure $lt $gen $([$($captures)*])?]
+            $($mov)? |$self_arg, $($rest)*
+        )
+    };
+    (@closure $($rest:tt)*) => {
+        compile_error!("Generic closures passed to fix_fn may only capture variables listed as `[name: Type, ..]`!");
+    };
+}
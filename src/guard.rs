@@ -0,0 +1,138 @@
+use core::convert::Infallible;
+
+/// Check run around every call of a guarded recursive closure, e.g. `fix_fn!(max_depth = .., ..)`.
+pub trait Guard {
+    /// Returned by the closure if [`Guard::enter`] refuses a call.
+    type Error;
+
+    /// Called before every call. Returning an error aborts the call.
+    fn enter(&self) -> Result<(), Self::Error>;
+
+    /// Called after every call that was entered, even if it panicked.
+    fn exit(&self) {}
+}
+
+impl<G: Guard + ?Sized> Guard for &G {
+    type Error = G::Error;
+
+    #[inline]
+    fn enter(&self) -> Result<(), G::Error> {
+        (**self).enter()
+    }
+
+    #[inline]
+    fn exit(&self) {
+        (**self).exit()
+    }
+}
+
+/// Runs `f` between [`Guard::enter`] and [`Guard::exit`].
+#[inline]
+pub fn guarded<G, R, F>(guard: &G, f: F) -> Result<R, G::Error>
+where
+    G: Guard + ?Sized,
+    F: FnOnce() -> Result<R, G::Error>,
+{
+    struct Exit<'a, G: Guard + ?Sized>(&'a G);
+
+    impl<G: Guard + ?Sized> Drop for Exit<'_, G> {
+        #[inline]
+        fn drop(&mut self) {
+            self.0.exit();
+        }
+    }
+
+    guard.enter()?;
+    let _exit = Exit(guard);
+    f()
+}
+
+/// Like [`guarded`], but for guards that never refuse a call.
+#[inline]
+pub fn observed<G, R, F>(guard: &G, f: F) -> R
+where
+    G: Guard<Error = Infallible> + ?Sized,
+    F: FnOnce() -> R,
+{
+    match guarded(guard, || Ok(f())) {
+        Ok(result) => result,
+        Err(never) => match never {},
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_guarded {
+    (
+        [$guard:expr] [$error:ty]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> ::core::result::Result<$ret_type, $error>;
+        }
+
+        struct HideFnImpl<G, F>(G, F)
+        where
+            G: $crate::__private::Guard<Error = $error>,
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> ::core::result::Result<$ret_type, $error>;
+
+        impl<G, F> HideFn for HideFnImpl<G, F>
+        where
+            G: $crate::__private::Guard<Error = $error>,
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> ::core::result::Result<$ret_type, $error>,
+        {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> ::core::result::Result<$ret_type, $error> {
+                $crate::__private::guarded(&self.0, || self.1(self, $($arg_name ,)*))
+            }
+        }
+
+        let inner = HideFnImpl(
+            $guard,
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> ::core::result::Result<$ret_type, $error> {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                ::core::result::Result::Ok({
+                    $body
+                })
+            }
+        );
+
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> ::core::result::Result<$ret_type, $error> {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        [$guard:expr] [$error:ty]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        [$guard:expr] [$error:ty]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$guard:expr] [$error:ty]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$guard:expr] [$error:ty] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_guarded] [[$guard] [$error]] $($mov)? |$self_arg, $($rest)*)
+    };
+}
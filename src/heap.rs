@@ -0,0 +1,140 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+/// Shared state between the driver of [`run`] and the self handle of a
+/// `fix_fn!(heap, ..)` closure.
+pub struct Frames<A, R> {
+    request: Cell<Option<A>>,
+    result: Cell<Option<R>>,
+}
+
+impl<A, R> Frames<A, R> {
+    /// Suspends the calling frame until the driver ran the closure with `args`.
+    #[inline]
+    pub fn call(&self, args: A) -> Call<'_, A, R> {
+        Call {
+            frames: self,
+            args: Some(args),
+        }
+    }
+}
+
+/// Future returned by the self handle of a `fix_fn!(heap, ..)` closure.
+pub struct Call<'a, A, R> {
+    frames: &'a Frames<A, R>,
+    args: Option<A>,
+}
+
+// the arguments are never pinned
+impl<A, R> Unpin for Call<'_, A, R> {}
+
+impl<A, R> Future for Call<'_, A, R> {
+    type Output = R;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<R> {
+        match self.args.take() {
+            Some(args) => {
+                let pending = self.frames.request.replace(Some(args));
+                assert!(
+                    pending.is_none(),
+                    "self calls in fix_fn!(heap, ..) must be awaited one at a time"
+                );
+                Poll::Pending
+            }
+            None => Poll::Ready(
+                self.frames
+                    .result
+                    .take()
+                    .expect("self call in fix_fn!(heap, ..) was polled after completion"),
+            ),
+        }
+    }
+}
+
+/// Runs `body` with `args`, keeping every suspended self call in a heap-allocated stack.
+pub fn run<A, R, F>(body: &F, args: A) -> R
+where
+    F: AsyncFn(&Frames<A, R>, A) -> R,
+{
+    let frames = Frames {
+        request: Cell::new(None),
+        result: Cell::new(None),
+    };
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut stack: Vec<Pin<Box<dyn Future<Output = R> + '_>>> = Vec::new();
+    stack.push(Box::pin(body(&frames, args)));
+
+    loop {
+        let top = stack.last_mut().expect("stack of fix_fn!(heap, ..) is never empty");
+        match top.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => {
+                stack.pop();
+                if stack.is_empty() {
+                    return result;
+                }
+                frames.result.set(Some(result));
+            }
+            Poll::Pending => {
+                let args = frames
+                    .request
+                    .take()
+                    .expect("fix_fn!(heap, ..) closures may only await calls of their self handle");
+                stack.push(Box::pin(body(&frames, args)));
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_heap {
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        let inner = async $($mov)? |
+            frames: &$crate::__private::HeapFrames<($($arg_type ,)*), $ret_type>,
+            ($($arg_name ,)*): ($($arg_type ,)*),
+        | -> $ret_type {
+            let $self_arg = |$($arg_name : $arg_type ),*| frames.call(($($arg_name ,)*));
+            {
+                $body
+            }
+        };
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            $crate::__private::run_heap(&inner, ($($arg_name ,)*))
+        }
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_heap] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,72 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_higher_ranked {
+    (
+        [$($lt:lifetime),+]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call<$($lt),+>(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F: for<$($lt),+> Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type>(F);
+
+        impl<F: for<$($lt),+> Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type> HideFn for HideFnImpl<F> {
+            #[inline]
+            fn call<$($lt),+>(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                self.0(self, $($arg_name ,)*)
+            }
+        }
+
+        // the lifetimes cannot be named in closure signatures,
+        // so the signatures are inferred from this bound instead
+        struct HigherRanked<F: for<$($lt),+> Fn($($arg_type ,)*) -> $ret_type>(F);
+
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name ,)*| {
+                let HigherRanked($self_arg) = HigherRanked(|$($arg_name ,)*| $self_arg.call($($arg_name ,)*));
+                {
+                    $body
+                }
+            }
+        );
+
+        let HigherRanked(outer) = HigherRanked(
+            #[inline]
+            move |$($arg_name ,)*| inner.call($($arg_name ,)*)
+        );
+        outer
+    }};
+    (
+        [$($lt:lifetime),+]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        [$($lt:lifetime),+]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$($lt:lifetime),+]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$($lt:lifetime),+] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_higher_ranked] [[$($lt),+]] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,153 @@
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::depth::{DepthExceeded, DepthLimit};
+use crate::guard::guarded;
+
+/// Middleware that runs around every call of an open recursive closure, see
+/// [`Open::layer`](crate::Open::layer).
+///
+/// `Args` is the tuple of all arguments of a call and `R` its result.
+pub trait RecursionLayer<Args, R> {
+    /// Handles a call with the arguments `args`. `next` passes the call on to the inner
+    /// layers and finally to the step function. A layer may also answer the call
+    /// without calling `next`, or call it with different arguments.
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> R) -> R;
+}
+
+impl<L, Args, R> RecursionLayer<Args, R> for &L
+where
+    L: RecursionLayer<Args, R> + ?Sized,
+{
+    #[inline]
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> R) -> R {
+        (**self).call(args, next)
+    }
+}
+
+/// [`RecursionLayer`] that caches results, like [`fix_fn_memo!`](crate::fix_fn_memo!).
+///
+/// The argument tuple must implement [`Hash`], [`Eq`] and [`Clone`]. The result must
+/// implement [`Clone`]. Requires the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Memoize<Args, R> {
+    cache: RefCell<HashMap<Args, R>>,
+}
+
+#[cfg(feature = "std")]
+impl<Args, R> Memoize<Args, R> {
+    /// Creates a layer with an empty cache.
+    pub fn new() -> Self {
+        Memoize {
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Args, R> Default for Memoize<Args, R> {
+    fn default() -> Self {
+        Memoize::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Args, R> RecursionLayer<Args, R> for Memoize<Args, R>
+where
+    Args: Hash + Eq + Clone,
+    R: Clone,
+{
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> R) -> R {
+        let cached = self.cache.borrow().get(&args).cloned();
+        if let Some(result) = cached {
+            return result;
+        }
+
+        let result = next(args.clone());
+        self.cache.borrow_mut().insert(args, result.clone());
+        result
+    }
+}
+
+/// [`RecursionLayer`] that limits the depth of the recursion, like
+/// `fix_fn!(max_depth = .., ..)`.
+///
+/// The step function has to return a `Result` whose error can be created from
+/// [`DepthExceeded`]. Calls that would nest deeper than the limit return that error
+/// instead of running. The top-level call has depth 1.
+pub struct MaxDepth {
+    limit: DepthLimit,
+}
+
+impl MaxDepth {
+    /// Creates a layer that allows a depth of up to `limit`.
+    pub fn new(limit: usize) -> Self {
+        MaxDepth {
+            limit: DepthLimit::new(limit),
+        }
+    }
+}
+
+impl<Args, T, E> RecursionLayer<Args, Result<T, E>> for MaxDepth
+where
+    E: From<DepthExceeded>,
+{
+    #[inline]
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> Result<T, E>) -> Result<T, E> {
+        match guarded(&self.limit, || Ok(next(args))) {
+            Ok(result) => result,
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+/// [`RecursionLayer`] that passes the depth and the arguments of every call to a closure
+/// before the call runs, e.g. to log them. The top-level call has depth 1.
+pub struct Inspect<F> {
+    f: F,
+    depth: Cell<usize>,
+}
+
+impl<F> Inspect<F> {
+    /// Creates a layer that calls `f` with the depth and the arguments of every call.
+    pub fn new(f: F) -> Self {
+        Inspect {
+            f,
+            depth: Cell::new(0),
+        }
+    }
+}
+
+impl<F, Args, R> RecursionLayer<Args, R> for Inspect<F>
+where
+    F: Fn(usize, &Args),
+{
+    #[inline]
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> R) -> R {
+        struct Exit<'a>(&'a Cell<usize>);
+
+        impl Drop for Exit<'_> {
+            #[inline]
+            fn drop(&mut self) {
+                self.0.set(self.0.get() - 1);
+            }
+        }
+
+        let depth = self.depth.get() + 1;
+        self.depth.set(depth);
+        let _exit = Exit(&self.depth);
+        (self.f)(depth, &args);
+        next(args)
+    }
+}
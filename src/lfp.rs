@@ -0,0 +1,471 @@
+use core::cell::{Cell, RefCell};
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::thread;
+use std::vec::Vec;
+
+enum State {
+    /// The value is final.
+    Done,
+    /// The call is running at the given index of the stack.
+    InProgress { index: usize },
+    /// The value was computed from the value of a still running call during the given
+    /// iteration. `frame` leads to that call, see [`Link`].
+    Provisional { epoch: u64, frame: usize },
+}
+
+/// Where a call that provisional values depend on went. Calls are identified by frame
+/// ids that are never reused, unlike their stack indices.
+enum Link {
+    /// The call is running at the given index of the stack.
+    Running(usize),
+    /// The call returned a provisional value that depends on the call with this frame id.
+    Merged(usize),
+}
+
+struct Running {
+    frame: usize,
+    /// Lowest stack index read by this call.
+    lowlink: usize,
+}
+
+struct Entry<R> {
+    value: R,
+    state: State,
+}
+
+/// Cache of a [`fix_fn_lfp!`](crate::fix_fn_lfp!) closure.
+///
+/// Calls that are still running form a stack. Reentering a running call returns its
+/// current value and remembers the lowest stack index that was read this way. A call
+/// that read a running call further down the stack belongs to the same strongly
+/// connected component of calls as that one, so its value stays provisional. The
+/// bottommost call of the component, the head, repeats its body until no value in the
+/// component changes anymore. Then it marks the provisional values computed in its last
+/// iteration as final and discards older ones, which may stem from outdated values.
+/// These are collected in a log while the head runs.
+pub struct Lfp<Args, R, B, J> {
+    entries: RefCell<HashMap<Args, Entry<R>>>,
+    stack: RefCell<Vec<Running>>,
+    frames: RefCell<Vec<Link>>,
+    log: RefCell<Vec<Args>>,
+    changes: Cell<u64>,
+    epoch: Cell<u64>,
+    bottom: B,
+    join: J,
+}
+
+impl<Args, R, B, J> Lfp<Args, R, B, J>
+where
+    Args: Hash + Eq + Clone,
+    R: Clone + PartialEq,
+    B: Fn() -> R,
+    J: Fn(R, R) -> R,
+{
+    pub fn new(bottom: B, join: J) -> Self {
+        Lfp {
+            entries: RefCell::new(HashMap::new()),
+            stack: RefCell::new(Vec::new()),
+            frames: RefCell::new(Vec::new()),
+            log: RefCell::new(Vec::new()),
+            changes: Cell::new(0),
+            epoch: Cell::new(0),
+            bottom,
+            join,
+        }
+    }
+
+    /// Returns the value for `args`, computing it with `body` if necessary.
+    pub fn call(&self, args: Args, body: &dyn Fn(Args) -> R) -> R {
+        if let Some(value) = self.lookup(&args) {
+            return value;
+        }
+
+        let index = self.stack.borrow().len();
+        let _reset = if index == 0 { Some(Reset(self)) } else { None };
+        {
+            let mut entries = self.entries.borrow_mut();
+            let state = State::InProgress { index };
+            match entries.get_mut(&args) {
+                // a provisional value of an earlier iteration is the starting point
+                Some(entry) => entry.state = state,
+                None => {
+                    let value = (self.bottom)();
+                    entries.insert(args.clone(), Entry { value, state });
+                }
+            }
+        }
+        let frame = {
+            let mut frames = self.frames.borrow_mut();
+            frames.push(Link::Running(index));
+            frames.len() - 1
+        };
+        self.stack.borrow_mut().push(Running {
+            frame,
+            lowlink: usize::MAX,
+        });
+        let log_start = self.log.borrow().len();
+
+        loop {
+            let changes = self.changes.get();
+            let iteration = self.epoch.get();
+            let result = body(args.clone());
+            let lowlink = self.stack.borrow().last().unwrap().lowlink;
+
+            let mut entries = self.entries.borrow_mut();
+            let entry = entries.get_mut(&args).unwrap();
+            let value = (self.join)(entry.value.clone(), result);
+            if value != entry.value {
+                entry.value = value.clone();
+                self.changes.set(self.changes.get() + 1);
+            }
+
+            if lowlink < index {
+                // part of the component of a call further down
+                let mut stack = self.stack.borrow_mut();
+                let head = stack[lowlink].frame;
+                self.frames.borrow_mut()[frame] = Link::Merged(head);
+                entry.state = State::Provisional {
+                    epoch: self.epoch.get(),
+                    frame: head,
+                };
+                drop(entries);
+                self.log.borrow_mut().push(args);
+                stack.pop();
+                Self::read(&mut stack, lowlink);
+                return value;
+            }
+
+            if lowlink == index && self.changes.get() != changes {
+                // head of a component that has not settled yet
+                self.epoch.set(self.epoch.get() + 1);
+                self.stack.borrow_mut().last_mut().unwrap().lowlink = usize::MAX;
+                continue;
+            }
+
+            entry.state = State::Done;
+            for args in self.log.borrow_mut().drain(log_start..) {
+                let stale = match entries.get_mut(&args) {
+                    Some(entry) => match entry.state {
+                        State::Provisional { epoch, .. } if epoch >= iteration => {
+                            entry.state = State::Done;
+                            false
+                        }
+                        State::Provisional { .. } => true,
+                        _ => false,
+                    },
+                    None => false,
+                };
+                if stale {
+                    // computed from an outdated value and not needed by the last
+                    // iteration, so it is computed again when it is needed
+                    entries.remove(&args);
+                }
+            }
+            let mut stack = self.stack.borrow_mut();
+            stack.pop();
+            if stack.is_empty() {
+                self.frames.borrow_mut().clear();
+            }
+            return value;
+        }
+    }
+
+    /// Returns the value for `args` if it doesn't need to be computed (again).
+    fn lookup(&self, args: &Args) -> Option<R> {
+        let entries = self.entries.borrow();
+        let entry = entries.get(args)?;
+        let read = match entry.state {
+            State::Done => None,
+            State::InProgress { index } => Some(index),
+            State::Provisional { epoch, frame } if epoch == self.epoch.get() => {
+                Some(self.resolve(frame))
+            }
+            State::Provisional { .. } => return None,
+        };
+        if let Some(index) = read {
+            Self::read(&mut self.stack.borrow_mut(), index);
+        }
+        Some(entry.value.clone())
+    }
+
+    /// Returns the stack index of the running call that `frame` was merged into.
+    fn resolve(&self, frame: usize) -> usize {
+        let mut frames = self.frames.borrow_mut();
+        let mut current = frame;
+        let index = loop {
+            match frames[current] {
+                Link::Running(index) => break index,
+                Link::Merged(next) => current = next,
+            }
+        };
+        // shorten the path for later lookups
+        let head = self.stack.borrow()[index].frame;
+        let mut current = frame;
+        while let Link::Merged(next) = frames[current] {
+            frames[current] = Link::Merged(head);
+            current = next;
+        }
+        index
+    }
+
+    fn read(stack: &mut [Running], index: usize) {
+        if let Some(running) = stack.last_mut() {
+            running.lowlink = running.lowlink.min(index);
+        }
+    }
+}
+
+/// Discards the unfinished state if the top-level call panics.
+struct Reset<'a, Args, R, B, J>(&'a Lfp<Args, R, B, J>);
+
+impl<Args, R, B, J> Drop for Reset<'_, Args, R, B, J> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let lfp = self.0;
+            lfp.stack.borrow_mut().clear();
+            lfp.frames.borrow_mut().clear();
+            lfp.log.borrow_mut().clear();
+            lfp.entries
+                .borrow_mut()
+                .retain(|_, entry| matches!(entry.state, State::Done));
+        }
+    }
+}
+
+/// Like [`fix_fn_memo!`](crate::fix_fn_memo!), but computes the least fixpoint of cyclic
+/// recursive definitions.
+///
+/// Some recursive definitions depend on themselves, like the nullability of grammar
+/// symbols, dataflow facts or reachability in a graph. With plain recursion, such a
+/// definition never terminates. `fix_fn_lfp!(bottom = value, |..| -> R { .. })` caches
+/// all results like `fix_fn_memo!`. A self call that reenters a call that is still
+/// running returns the current approximation of its result instead of recursing, which
+/// starts as `value`. Once the calls that depend on each other this way are done, they
+/// are evaluated again with the new approximations until no result changes anymore.
+///
+/// `fix_fn_lfp!(bottom = value, join = join, |..| -> R { .. })` combines the previous
+/// approximation and the new result of the body with the closure `join`, e.g. the union
+/// of two sets. Without `join`, the new result replaces the previous one.
+///
+/// For the iteration to terminate, the body has to be monotone: it must not produce a
+/// smaller result when self calls return larger results, in the order given by `join`.
+/// There may only be finitely many different results above `bottom`, e.g. `bool`
+/// starting from `false` or sets of elements of a finite universe.
+///
+/// All parameter types except the first must implement [`Hash`], [`Eq`] and [`Clone`].
+/// The result type must implement [`Clone`] and [`PartialEq`].
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_lfp;
+/// use std::collections::BTreeSet;
+///
+/// enum Symbol {
+///     Terminal(char),
+///     Nonterminal(usize),
+/// }
+/// use Symbol::*;
+///
+/// // S -> A B
+/// // A -> S 'a' | ε
+/// // B -> B 'b' | A
+/// // C -> C 'c' | 'c'
+/// let grammar = vec![
+///     vec![vec![Nonterminal(1), Nonterminal(2)]],
+///     vec![vec![Nonterminal(0), Terminal('a')], vec![]],
+///     vec![vec![Nonterminal(2), Terminal('b')], vec![Nonterminal(1)]],
+///     vec![vec![Nonterminal(3), Terminal('c')], vec![Terminal('c')]],
+/// ];
+///
+/// let nullable = fix_fn_lfp!(bottom = false, |nullable, nonterminal: usize| -> bool {
+///     grammar[nonterminal].iter().any(|production| {
+///         production.iter().all(|symbol| match *symbol {
+///             Terminal(_) => false,
+///             Nonterminal(n) => nullable(n),
+///         })
+///     })
+/// });
+///
+/// assert_eq!((0..4).map(&nullable).collect::<Vec<_>>(), [true, true, true, false]);
+///
+/// let first = fix_fn_lfp!(
+///     bottom = BTreeSet::new(),
+///     join = |old: BTreeSet<char>, new| &old | &new,
+///     |first, nonterminal: usize| -> BTreeSet<char> {
+///         let mut set = BTreeSet::new();
+///         for production in &grammar[nonterminal] {
+///             for symbol in production {
+///                 match *symbol {
+///                     Terminal(t) => {
+///                         set.insert(t);
+///                         break;
+///                     }
+///                     Nonterminal(n) => {
+///                         set.extend(first(n));
+///                         if !nullable(n) {
+///                             break;
+///                         }
+///                     }
+///                 }
+///             }
+///         }
+///         set
+///     }
+/// );
+///
+/// assert_eq!(first(0).into_iter().collect::<String>(), "ab");
+/// assert_eq!(first(2).into_iter().collect::<String>(), "ab");
+/// assert_eq!(first(3).into_iter().collect::<String>(), "c");
+/// ```
+///
+/// Results don't depend on the order of the calls, even if a component of calls that
+/// depend on each other is entered from several places:
+///
+/// ```
+/// use fix_fn::fix_fn_lfp;
+///
+/// let make = || {
+///     fix_fn_lfp!(bottom = false, |f, n: u32| -> bool {
+///         match n {
+///             0 => {
+///                 let a = f(1);
+///                 let c = f(3);
+///                 a | c | true
+///             }
+///             1 => {
+///                 let b = f(2);
+///                 let h = f(0);
+///                 b | h
+///             }
+///             2 => f(1),
+///             3 => f(2),
+///             _ => unreachable!(),
+///         }
+///     })
+/// };
+///
+/// let f = make();
+/// assert!(f(0));
+/// assert!(f(3));
+/// assert!(make()(3));
+///
+/// // `f(1)` is only computed in the first iteration of `f(0)`
+/// let make = || {
+///     fix_fn_lfp!(bottom = false, |f, n: u32| -> bool {
+///         match n {
+///             0 => {
+///                 if f(0) {
+///                     true
+///                 } else {
+///                     let _ = f(1);
+///                     true
+///                 }
+///             }
+///             1 => f(0),
+///             _ => unreachable!(),
+///         }
+///     })
+/// };
+///
+/// let f = make();
+/// assert!(f(0));
+/// assert!(f(1));
+/// assert!(make()(1));
+/// ```
+#[macro_export]
+macro_rules! fix_fn_lfp {
+    (bottom = $bottom:expr, join = $join:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_lfp!([$bottom] [$join] $($rest)*)
+    };
+    (bottom = $bottom:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_lfp!([$bottom] [|_, new| new] $($rest)*)
+    };
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_lfp needs a bottom value, as in `fix_fn_lfp!(bottom = .., |..| -> R { .. })`!");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_lfp {
+    (
+        [$bottom:expr] [$join:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F, B, J>(F, $crate::__private::Lfp<($($arg_type ,)*), $ret_type, B, J>)
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type,
+            B: Fn() -> $ret_type,
+            J: Fn($ret_type, $ret_type) -> $ret_type;
+
+        impl<F, B, J> HideFn for HideFnImpl<F, B, J>
+        where
+            F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type,
+            B: Fn() -> $ret_type,
+            J: Fn($ret_type, $ret_type) -> $ret_type,
+        {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                self.1.call(($($arg_name ,)*), &|($($arg_name ,)*)| self.0(self, $($arg_name ,)*))
+            }
+        }
+
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            },
+            $crate::__private::Lfp::<($($arg_type ,)*), $ret_type, _, _>::new(|| $bottom, $join),
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        [$bottom:expr] [$join:expr]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_lfp needs return type!");
+    };
+    (
+        [$bottom:expr] [$join:expr]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$bottom:expr] [$join:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$bottom:expr] [$join:expr] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_lfp] [[$bottom] [$join]] $($mov)? |$self_arg, $($rest)*)
+    };
+    ([$bottom:expr] [$join:expr] $($rest:tt)*) => {
+        compile_error!("fix_fn_lfp expects a closure of the form `|f, arg: Type, ..| -> R { .. }`!");
+    };
+}
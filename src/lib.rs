@@ -1,4 +1,95 @@
-#[no_std]
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+mod async_fn;
+mod cancel;
+mod combinator;
+#[cfg(feature = "alloc")]
+mod cycle;
+mod depth;
+mod fix;
+mod fix_point;
+mod fn_mut;
+mod fuel;
+mod generic;
+mod guard;
+#[cfg(feature = "alloc")]
+mod heap;
+mod higher_ranked;
+mod layer;
+#[cfg(feature = "std")]
+mod lfp;
+#[cfg(feature = "std")]
+mod memo;
+mod mutual;
+mod open;
+#[cfg(feature = "std")]
+mod par;
+mod patterns;
+#[cfg(feature = "std")]
+mod stack;
+mod static_fn;
+#[cfg(feature = "alloc")]
+mod stats;
+mod tail;
+#[cfg(feature = "alloc")]
+mod trace;
+
+pub use cancel::Cancelled;
+#[cfg(feature = "std")]
+pub use cancel::{DeadlineExceeded, Interrupted};
+pub use combinator::{fix, fix2, fix3, fix4, fix5, fix6};
+#[cfg(feature = "alloc")]
+pub use cycle::Cycle;
+pub use depth::DepthExceeded;
+pub use fix::Fix;
+pub use fix_point::{fix_point, Convergence, Equal, FixPoint, Iterated};
+pub use fuel::{Fuel, OutOfFuel};
+#[cfg(feature = "std")]
+pub use layer::Memoize;
+pub use layer::{Inspect, MaxDepth, RecursionLayer};
+pub use open::{Layered, Open, Step};
+#[cfg(feature = "std")]
+pub use par::ThreadBudget;
+#[cfg(feature = "std")]
+pub use stack::with_stack_size;
+#[cfg(feature = "alloc")]
+pub use stats::Stats;
+pub use tail::Tail;
+#[cfg(feature = "alloc")]
+pub use trace::{CallNode, CallTree, DebugArgs};
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub use crate::async_fn::{BoxFuture, LocalBoxFuture};
+    pub use crate::cancel::Cancellation;
+    #[cfg(feature = "std")]
+    pub use crate::cancel::{CancellationOrDeadline, Deadline};
+    #[cfg(feature = "alloc")]
+    pub use crate::cycle::CycleTracker;
+    pub use crate::depth::DepthLimit;
+    pub use crate::guard::{guarded, observed, Guard};
+    #[cfg(feature = "alloc")]
+    pub use crate::heap::{run as run_heap, Frames as HeapFrames};
+    #[cfg(feature = "std")]
+    pub use crate::lfp::Lfp;
+    #[cfg(feature = "alloc")]
+    pub use crate::trace::debug_args;
+    #[cfg(feature = "alloc")]
+    pub use alloc::boxed::Box;
+    #[cfg(feature = "alloc")]
+    pub use alloc::rc::Rc;
+    #[cfg(feature = "alloc")]
+    pub use alloc::sync::Arc;
+    #[cfg(feature = "std")]
+    pub use std::collections::HashMap;
+}
 
 /// Takes a closure definition where the first parameter will be a [`Fn`] to the closure itself.
 /// Returns a recursive closure with the same signature, except the first parameter will be
@@ -32,8 +123,519 @@
 /// // resulting lambda only has the `i: u32` parameter
 /// assert_eq!(fib(7), 13);
 /// ```
+///
+/// # Patterns
+///
+/// Like in a regular closure, additional parameters can be irrefutable patterns,
+/// including `mut` and `ref` bindings. The resulting closure and the self handle take
+/// the whole value and the pattern is only matched inside the body.
+///
+/// ```
+/// use fix_fn::fix_fn;
+///
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let digits = fix_fn!(|digits, n: u32, mut acc: Vec<u32>| -> Vec<u32> {
+///     acc.insert(0, n % 10);
+///     if n < 10 { acc } else { digits(n / 10, acc) }
+/// });
+/// assert_eq!(digits(2024, Vec::new()), [2, 0, 2, 4]);
+///
+/// let sum = fix_fn!(|sum, values: &[u32], (lo, hi): (usize, usize)| -> u32 {
+///     match hi - lo {
+///         0 => 0,
+///         1 => values[lo],
+///         len => sum(values, (lo, lo + len / 2)) + sum(values, (lo + len / 2, hi)),
+///     }
+/// });
+/// assert_eq!(sum(&[1, 2, 3, 4, 5], (0, 5)), 15);
+///
+/// let steps = fix_fn!(|steps, Point { x, y }: Point| -> u32 {
+///     if x == 0 && y == 0 {
+///         0
+///     } else {
+///         1 + steps(Point { x: x - x.signum(), y: y - y.signum() })
+///     }
+/// });
+/// assert_eq!(steps(Point { x: 3, y: -5 }), 5);
+///
+/// let gcd = fix_fn!(|gcd, &(a, b): &(u32, u32), ref label: String| -> String {
+///     if b == 0 { format!("{}: {}", label, a) } else { gcd(&(b, a % b), label.clone()) }
+/// });
+/// assert_eq!(gcd(&(48, 18), "gcd".to_string()), "gcd: 6");
+/// ```
+///
+/// # Heap mode
+///
+/// `fix_fn!(heap, |..| -> R { .. })` keeps the recursion off the native stack, so
+/// arbitrarily deep recursion works, even if it is not tail recursive. The body is run
+/// as an async closure and every call of the self handle returns a future that has to
+/// be `.await`ed right away. Awaiting suspends the calling frame on an explicit,
+/// heap-allocated stack until the result of the self call is ready. No async runtime
+/// is needed and the resulting closure is a normal synchronous closure.
+///
+/// This costs a heap allocation per call and is considerably slower than the normal
+/// mode. Only self calls may be awaited in the body and they must not be polled
+/// concurrently, e.g. with `join`. Heap mode requires the `alloc` feature.
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use fix_fn::fix_fn;
+///
+/// let depth = fix_fn!(heap, |depth, n: u64| -> u64 {
+///     if n == 0 {
+///         0
+///     } else {
+///         // not a tail call, but still runs in constant native stack space
+///         depth(n - 1).await + 1
+///     }
+/// });
+///
+/// assert_eq!(depth(1_000_000), 1_000_000);
+///
+/// let fib = fix_fn!(heap, |fib, i: u32| -> u32 {
+///     if i <= 1 {
+///         i
+///     } else {
+///         fib(i - 1).await + fib(i - 2).await
+///     }
+/// });
+///
+/// assert_eq!(fib(20), 6765);
+/// ```
+///
+/// # Static dispatch
+///
+/// Recursive calls go through a trait object. In optimized builds, the compiler
+/// usually resolves these calls statically, so a recursive closure is about as fast as a
+/// recursive function. `cargo bench` compares the different forms with a hand-written
+/// function.
+///
+/// `fix_fn!(static, |..| -> R { .. })` guarantees static dispatch by turning the body
+/// into a nested function, so recursive calls are plain function calls. As a
+/// function cannot capture its environment, the body may not use any local variables
+/// of the surrounding scope, and `move` is rejected. The result is a function item,
+/// which implements [`Fn`], [`Copy`] and [`Send`].
+///
+/// ```
+/// use fix_fn::fix_fn;
+///
+/// let fib = fix_fn!(static, |fib, i: u32| -> u64 {
+///     if i <= 1 {
+///         i as u64
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// });
+///
+/// assert_eq!(fib(30), 832_040);
+/// ```
+///
+/// ```compile_fail
+/// use fix_fn::fix_fn;
+///
+/// let offset = 1;
+/// // `offset` cannot be captured
+/// let fib = fix_fn!(static, |fib, i: u32| -> u32 {
+///     if i <= 1 { i + offset } else { fib(i - 1) + fib(i - 2) }
+/// });
+/// ```
+///
+/// # Lifetimes
+///
+/// If the result borrows from the parameters, e.g. `|f, node: &Node, key: u32| -> Option<&Node>`,
+/// the lifetimes have to be declared with `for<'a, ..>` in front of the closure
+/// definition and used in the parameter and result types. The resulting closure works
+/// for all choices of these lifetimes.
+///
+/// ```
+/// use fix_fn::fix_fn;
+///
+/// struct Node {
+///     key: u32,
+///     children: Vec<Node>,
+/// }
+///
+/// let find = fix_fn!(for<'a> |find, node: &'a Node, key: u32| -> Option<&'a Node> {
+///     if node.key == key {
+///         Some(node)
+///     } else {
+///         node.children.iter().find_map(|child| find(child, key))
+///     }
+/// });
+///
+/// let tree = Node {
+///     key: 1,
+///     children: vec![
+///         Node { key: 2, children: vec![] },
+///         Node { key: 3, children: vec![Node { key: 4, children: vec![] }] },
+///     ],
+/// };
+///
+/// assert_eq!(find(&tree, 4).map(|node| node.key), Some(4));
+/// assert!(find(&tree, 5).is_none());
+/// ```
+///
+/// # Generic closures
+///
+/// `fix_fn!(<T: Bound, ..> |..| -> R { .. })` creates a recursive closure that is generic
+/// over the given type parameters. As closures cannot be generic, the result is a value
+/// with a generic `call` method instead. The self handle is that value, too, so
+/// recursive calls are written as `f.call(..)`, and they may use different type
+/// arguments than the current call (polymorphic recursion).
+///
+/// The body cannot capture its environment implicitly. Captured variables are listed
+/// with their types in brackets in front of the closure, as `[name: Type]` to move a
+/// variable of the same name into the result or `[name: Type = expr]` to initialize it
+/// with an expression. Inside the body, captures are available by reference. Lifetime
+/// parameters in front of the type parameters can be used in the types of the captures.
+///
+/// ```
+/// use fix_fn::fix_fn;
+/// use std::cell::Cell;
+///
+/// let merges = Cell::new(0);
+/// let sort = fix_fn!(<'a, T: Ord + Clone> [merges: &'a Cell<usize> = &merges] |sort, v: Vec<T>| -> Vec<T> {
+///     if v.len() <= 1 {
+///         return v;
+///     }
+///     let (left, right) = v.split_at(v.len() / 2);
+///     let (left, right) = (sort.call(left.to_vec()), sort.call(right.to_vec()));
+///     merges.set(merges.get() + 1);
+///
+///     let mut result = Vec::with_capacity(v.len());
+///     let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+///     while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+///         result.push(if l <= r { left.next() } else { right.next() }.unwrap());
+///     }
+///     result.extend(left.chain(right));
+///     result
+/// });
+///
+/// assert_eq!(sort.call(vec![3, 1, 2]), [1, 2, 3]);
+/// assert_eq!(sort.call(vec!["b", "c", "a"]), ["a", "b", "c"]);
+/// assert_eq!(merges.get(), 4);
+///
+/// // a recursive call with a different type argument
+/// let nest = fix_fn!(<T: std::fmt::Debug> |nest, value: T, depth: usize| -> String {
+///     if depth == 0 {
+///         format!("{:?}", value)
+///     } else {
+///         nest.call(format!("[{:?}]", value), depth - 1)
+///     }
+/// });
+///
+/// assert_eq!(nest.call(1, 0), "1");
+/// assert_eq!(nest.call('x', 1), r#""['x']""#);
+/// ```
+///
+/// # Nameable type
+///
+/// `fix_fn!(Fix, |..| -> R { .. })` returns a [`Fix`] instead of a closure. Its type can
+/// be named, so it can be stored in struct fields or returned from functions. It is
+/// called with [`Fix::call`].
+///
+/// ```
+/// use fix_fn::fix_fn;
+///
+/// let fib = fix_fn!(Fix, |fib, i: u32| -> u32 {
+///     if i <= 1 {
+///         i
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// });
+///
+/// assert_eq!(fib.call(7), 13);
+/// ```
+///
+/// # Open recursion
+///
+/// `fix_fn!(open, |..| -> R { .. })` returns the body as an [`Open`] step function,
+/// whose self calls are not tied to the step itself yet. [`Open::layer`] adds
+/// [`RecursionLayer`]s that run around every call, like [`Memoize`], [`MaxDepth`],
+/// [`Inspect`] or a [`CallTree`], so these concerns don't have to be written into the
+/// body. [`Open::fix`] then ties the knot and returns a [`Fix`]. To test a single step,
+/// [`Open::call_with`] runs it with a stub for the self calls.
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use fix_fn::{fix_fn, CallTree, DepthExceeded, MaxDepth};
+///
+/// let calls = CallTree::new("ackermann");
+/// let ackermann = fix_fn!(open, |ackermann, m: u64, n: u64| -> Result<u64, DepthExceeded> {
+///     Ok(match (m, n) {
+///         (0, n) => n + 1,
+///         (m, 0) => ackermann(m - 1, 1)?,
+///         (m, n) => ackermann(m - 1, ackermann(m, n - 1)?)?,
+///     })
+/// })
+/// .layer(MaxDepth::new(1_000))
+/// .layer(&calls)
+/// .fix();
+///
+/// assert_eq!(ackermann.call(1, 1), Ok(3));
+/// assert_eq!(calls.to_ascii(), "\
+/// ackermann(1, 1) = Ok(3)
+/// |-- ackermann(1, 0) = Ok(2)
+/// |   `-- ackermann(0, 1) = Ok(2)
+/// `-- ackermann(0, 2) = Ok(3)
+/// ");
+///
+/// assert_eq!(ackermann.call(4, 1), Err(DepthExceeded { depth: 1_001, limit: 1_000 }));
+/// ```
+///
+/// # Depth limit
+///
+/// `fix_fn!(max_depth = limit, |..| -> R { .. })` limits how deep the recursion may nest.
+/// The resulting closure returns `Result<R, DepthExceeded>` and so does the self handle
+/// inside the body, so `?` can be used to abort the whole recursion once a call would
+/// nest deeper than `limit`. The body itself still evaluates to `R`, but an early
+/// `return` leaves the closure that returns the `Result`, so it has to return `Ok(..)`.
+/// The same applies to fuel, cancellation, deadlines and `fix_fn!(cycle, ..)`, which
+/// return a `Result` as well. The top-level call has depth 1.
+///
+/// ```
+/// use fix_fn::{fix_fn, DepthExceeded};
+///
+/// let countdown = fix_fn!(max_depth = 10, |countdown, n: u32| -> u32 {
+///     if n == 0 {
+///         return Ok(0);
+///     }
+///     countdown(n - 1)? + 1
+/// });
+///
+/// assert_eq!(countdown(5), Ok(5));
+/// assert_eq!(countdown(20), Err(DepthExceeded { depth: 11, limit: 10 }));
+///
+/// // nesting depth of untrusted input like "(()(()))"
+/// let nesting = fix_fn!(max_depth = 100, |nesting, input: &[u8]| -> (usize, usize) {
+///     let mut consumed = 0;
+///     let mut deepest = 0;
+///     while input.get(consumed) == Some(&b'(') {
+///         let (depth, len) = nesting(&input[consumed + 1..])?;
+///         deepest = deepest.max(depth + 1);
+///         consumed += len + 2;
+///     }
+///     (deepest, consumed)
+/// });
+///
+/// assert_eq!(nesting(b"(()(()))"), Ok((3, 8)));
+///
+/// let adversarial = [b"(".repeat(1_000_000), b")".repeat(1_000_000)].concat();
+/// assert_eq!(
+///     nesting(&adversarial),
+///     Err(DepthExceeded { depth: 101, limit: 100 })
+/// );
+/// ```
+///
+/// # Fuel
+///
+/// `fix_fn!(fuel = fuel, |..| -> R { .. })` limits the total number of calls, which
+/// also bounds recursions that are shallow but exponential. `fuel` is a [`Fuel`] or a
+/// reference to one. Every call, including the top-level call, consumes one unit.
+/// Like with a depth limit, the resulting closure and the self handle return
+/// `Result<R, OutOfFuel>`. Pass the fuel by reference to query it in the body.
+///
+/// ```
+/// use fix_fn::{fix_fn, Fuel, OutOfFuel};
+///
+/// let fuel = Fuel::new(1_000);
+/// let fib = fix_fn!(fuel = &fuel, |fib, i: u32| -> u64 {
+///     if i <= 1 {
+///         i as u64
+///     } else if fuel.remaining() < 10 {
+///         // not enough fuel left to be exact
+///         0
+///     } else {
+///         fib(i - 1)? + fib(i - 2)?
+///     }
+/// });
+///
+/// assert_eq!(fib(10), Ok(55));
+/// assert_eq!(fuel.consumed(), 177);
+///
+/// let fuel = Fuel::new(1_000);
+/// let fib = fix_fn!(fuel = &fuel, |fib, i: u32| -> u64 {
+///     if i <= 1 { i as u64 } else { fib(i - 1)? + fib(i - 2)? }
+/// });
+///
+/// assert_eq!(fib(50), Err(OutOfFuel { calls: 1_000 }));
+/// assert_eq!(fuel.remaining(), 0);
+/// ```
+///
+/// # Cancellation and deadlines
+///
+/// `fix_fn!(cancel = &flag, |..| -> R { .. })` stops the recursion once the
+/// [`AtomicBool`](core::sync::atomic::AtomicBool) `flag` is set, e.g. by another thread
+/// or a signal handler. Every call checks the flag first and returns `Err(Cancelled)`
+/// if it is set. `?` passes the error up, so the recursion unwinds promptly and
+/// normally, without panicking.
+///
+/// `fix_fn!(deadline = instant, |..| -> R { .. })` does the same once the
+/// [`Instant`] `instant` has passed and returns
+/// `Err(DeadlineExceeded)`. With `fix_fn!(cancel = &flag, deadline = instant, ..)` both
+/// are checked and the error is an [`Interrupted`]. Deadlines require the `std` feature.
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use fix_fn::{fix_fn, Cancelled, DeadlineExceeded};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::time::{Duration, Instant};
+///
+/// let cancel = AtomicBool::new(false);
+/// let fib = fix_fn!(cancel = &cancel, |fib, i: u32| -> u64 {
+///     if i <= 1 { i as u64 } else { fib(i - 1)? + fib(i - 2)? }
+/// });
+///
+/// assert_eq!(fib(20), Ok(6765));
+///
+/// // cancel from another thread while the search is running
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| {
+///         std::thread::sleep(Duration::from_millis(10));
+///         cancel.store(true, Ordering::Relaxed);
+///     });
+///     assert_eq!(fib(100), Err(Cancelled));
+/// });
+///
+/// let fib = fix_fn!(deadline = Instant::now() + Duration::from_millis(10), |fib, i: u32| -> u64 {
+///     if i <= 1 { i as u64 } else { fib(i - 1)? + fib(i - 2)? }
+/// });
+///
+/// assert_eq!(fib(100), Err(DeadlineExceeded));
+/// ```
+///
+/// # Cycle detection
+///
+/// `fix_fn!(cycle, |..| -> R { .. })` detects when a call reenters a call with equal
+/// arguments that is still running, which would otherwise recurse forever, e.g. when
+/// walking a graph with cycles. Instead of running, the reentering call returns
+/// `Err(Cycle { path })` with the arguments of all calls that form the cycle. Like with
+/// a depth limit, the resulting closure and the self handle return
+/// `Result<R, Cycle<Args>>`, where `Args` is the type of the only parameter or a tuple
+/// of all parameters.
+///
+/// `fix_fn!(cycle = fallback, |..| -> R { .. })` calls the closure `fallback` with the
+/// arguments of the reentering call instead and returns its result, so the result
+/// type stays `R`.
+///
+/// The parameters are compared with [`PartialEq`] and have to implement [`Clone`].
+/// Their types may not contain elided lifetimes. Cycle detection requires the `alloc`
+/// feature.
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use fix_fn::{fix_fn, Cycle};
+///
+/// let imports: &[&[usize]] = &[&[1, 2], &[2], &[3], &[1]];
+///
+/// let load_order = fix_fn!(cycle, |load_order, module: usize| -> Vec<usize> {
+///     let mut order = Vec::new();
+///     for &import in imports[module] {
+///         for dependency in load_order(import)? {
+///             if !order.contains(&dependency) {
+///                 order.push(dependency);
+///             }
+///         }
+///     }
+///     order.push(module);
+///     order
+/// });
+///
+/// assert_eq!(load_order(2), Err(Cycle { path: vec![2, 3, 1, 2] }));
+///
+/// // the number of modules reachable from a module, not counting modules on a cycle twice
+/// let reachable = fix_fn!(cycle = |_| 0, |reachable, module: usize| -> usize {
+///     1 + imports[module].iter().map(|&import| reachable(import)).sum::<usize>()
+/// });
+///
+/// assert_eq!(reachable(3), 3);
+/// ```
+///
+/// # Stack size
+///
+/// `fix_fn!(stack_size = bytes, |..| -> R { .. })` runs every top-level call on a new
+/// thread with a stack of the given size, see [`with_stack_size`]. Recursive calls stay
+/// on that thread. The caller blocks until the result is ready and a panic in the body
+/// is resumed in the caller. Everything captured by the closure has to be [`Sync`], and
+/// the parameters and the result have to be [`Send`]. Requires the `std` feature.
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use fix_fn::fix_fn;
+///
+/// let depth = fix_fn!(stack_size = 64 << 20, |depth, n: u64| -> u64 {
+///     if n == 0 { 0 } else { depth(n - 1) + 1 }
+/// });
+///
+/// assert_eq!(depth(100_000), 100_000);
+/// ```
+///
+// items of disabled features are linked on docs.rs
+#[cfg_attr(feature = "std", doc = "[`Memoize`]: crate::Memoize")]
+#[cfg_attr(not(feature = "std"), doc = "[`Memoize`]: https://docs.rs/fix_fn/latest/fix_fn/struct.Memoize.html")]
+#[cfg_attr(feature = "alloc", doc = "[`CallTree`]: crate::CallTree")]
+#[cfg_attr(not(feature = "alloc"), doc = "[`CallTree`]: https://docs.rs/fix_fn/latest/fix_fn/struct.CallTree.html")]
+#[cfg_attr(feature = "std", doc = "[`Instant`]: std::time::Instant")]
+#[cfg_attr(not(feature = "std"), doc = "[`Instant`]: https://doc.rust-lang.org/std/time/struct.Instant.html")]
+#[cfg_attr(feature = "std", doc = "[`Interrupted`]: crate::Interrupted")]
+#[cfg_attr(not(feature = "std"), doc = "[`Interrupted`]: https://docs.rs/fix_fn/latest/fix_fn/enum.Interrupted.html")]
+#[cfg_attr(feature = "std", doc = "[`with_stack_size`]: crate::with_stack_size")]
+#[cfg_attr(not(feature = "std"), doc = "[`with_stack_size`]: https://docs.rs/fix_fn/latest/fix_fn/fn.with_stack_size.html")]
 #[macro_export]
 macro_rules! fix_fn {
+    (Fix, $($rest:tt)*) => {
+        $crate::__fix_fn_fix!([$crate::Fix] $($rest)*)
+    };
+    (open, $($rest:tt)*) => {
+        $crate::__fix_fn_fix!([$crate::Open] $($rest)*)
+    };
+    (heap, $($rest:tt)*) => {
+        $crate::__fix_fn_heap!($($rest)*)
+    };
+    (for<$($lt:lifetime),+ $(,)?> $($rest:tt)*) => {
+        $crate::__fix_fn_higher_ranked!([$($lt),+] $($rest)*)
+    };
+    (< $($rest:tt)*) => {
+        $crate::__fix_fn_generic!(@lifetimes [] $($rest)*)
+    };
+    (static, $($rest:tt)*) => {
+        $crate::__fix_fn_static!($($rest)*)
+    };
+    (max_depth = $limit:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_guarded!(
+            [$crate::__private::DepthLimit::new($limit)] [$crate::DepthExceeded]
+            $($rest)*
+        )
+    };
+    (fuel = $fuel:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_guarded!([$fuel] [$crate::OutOfFuel] $($rest)*)
+    };
+    (cancel = $cancel:expr, deadline = $deadline:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_deadline!([$deadline] [$cancel] $($rest)*)
+    };
+    (deadline = $deadline:expr, cancel = $cancel:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_deadline!([$deadline] [$cancel] $($rest)*)
+    };
+    (cancel = $cancel:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_guarded!([$crate::__private::Cancellation($cancel)] [$crate::Cancelled] $($rest)*)
+    };
+    (deadline = $deadline:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_deadline!([$deadline] [] $($rest)*)
+    };
+    (cycle = $fallback:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_cycle!([$fallback] $($rest)*)
+    };
+    (cycle, $($rest:tt)*) => {
+        $crate::__fix_fn_cycle!([] $($rest)*)
+    };
+    (stack_size = $size:expr, $($rest:tt)*) => {
+        $crate::__fix_fn_stack_size!([$size] $($rest)*)
+    };
     (
         $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
             -> $ret_type:ty
@@ -89,4 +691,90 @@ macro_rules! fix_fn {
     ) => {
         compile_error!("All parameters except first need to have an explicit type annotation!");
     };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_heap {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn!(heap, ..) requires the `alloc` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_cycle {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn!(cycle, ..) requires the `alloc` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_stack_size {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn!(stack_size = .., ..) requires the `std` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_async {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_async! requires the `alloc` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_stats {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_stats! requires the `alloc` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_trace {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_trace! requires the `alloc` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_memo {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_memo! requires the `std` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_par {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_par! requires the `std` feature of fix_fn!");
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fix_fn_lfp {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_lfp! requires the `std` feature of fix_fn!");
+    };
 }
@@ -0,0 +1,112 @@
+/// Like [`fix_fn!`](crate::fix_fn!), but memoizes the results of the closure.
+///
+/// Results are cached keyed by the tuple of all parameters except the first. Every
+/// call, recursive or not, first looks up its arguments in the cache and only runs
+/// the body if no result was stored yet. The cache belongs to the returned closure, so
+/// it is shared by all calls to the closure and lives as long as the closure does.
+///
+/// All parameter types except the first must implement [`Hash`](core::hash::Hash),
+/// [`Eq`] and [`Clone`]. The result type must implement [`Clone`].
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_memo;
+/// use std::cell::Cell;
+///
+/// let calls = Cell::new(0);
+/// let fib = fix_fn_memo!(|fib, i: u32| -> u64 {
+///     calls.set(calls.get() + 1);
+///     if i <= 1 {
+///         i as u64
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// });
+///
+/// assert_eq!(fib(90), 2_880_067_194_370_816_120);
+/// // every value from 0 to 90 was computed exactly once
+/// assert_eq!(calls.get(), 91);
+///
+/// // later calls reuse the cache
+/// assert_eq!(fib(50), 12_586_269_025);
+/// assert_eq!(calls.get(), 91);
+/// ```
+#[macro_export]
+macro_rules! fix_fn_memo {
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type>(
+            F,
+            ::core::cell::RefCell<$crate::__private::HashMap<($($arg_type ,)*), $ret_type>>,
+        );
+
+        impl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type> HideFn for HideFnImpl<F> {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                let key = ($($arg_name ,)*);
+                let cached = self.1.borrow().get(&key).cloned();
+                if let Some(result) = cached {
+                    return result;
+                }
+
+                let ($($arg_name ,)*) = ::core::clone::Clone::clone(&key);
+                let result = self.0(self, $($arg_name ,)*);
+                self.1.borrow_mut().insert(key, ::core::clone::Clone::clone(&result));
+                result
+            }
+        }
+
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            },
+            ::core::default::Default::default(),
+        );
+
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_memo needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn_memo] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,215 @@
+/// Defines a group of closures that can call each other recursively.
+///
+/// Takes a list of `let` statements, each binding a closure definition to a name.
+/// Inside the body of every closure, all names of the group can be called like
+/// ordinary closures, including the closure's own name. After the macro, each name is
+/// bound to its closure in the surrounding scope.
+///
+/// Unlike [`fix_fn!`](crate::fix_fn!), the closures have no self parameter, as the
+/// names of the group are used for recursion. All parameters must be annotated with
+/// types and every closure needs a result-type annotation. Like with `fix_fn!`,
+/// parameters may be patterns, e.g. `mut acc: Vec<u32>` or `(x, y): (i32, i32)`.
+///
+/// The closures share the captured environment and `move` can be used on each of
+/// them. The resulting closures borrow the group, which is stored in a hidden local
+/// of the surrounding scope, so they cannot outlive that scope.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fns;
+///
+/// fix_fns! {
+///     let is_even = |n: u32| -> bool {
+///         n == 0 || is_odd(n - 1)
+///     };
+///     let is_odd = |n: u32| -> bool {
+///         n != 0 && is_even(n - 1)
+///     };
+/// }
+///
+/// assert!(is_even(10));
+/// assert!(is_odd(7));
+/// assert!(!is_odd(4));
+/// ```
+///
+/// A small recursive-descent evaluator:
+///
+/// ```
+/// use fix_fn::fix_fns;
+///
+/// let input: Vec<char> = "2*(3+4)+1".chars().collect();
+///
+/// fix_fns! {
+///     let expr = |pos: usize| -> (i64, usize) {
+///         let (mut value, mut pos) = term(pos);
+///         while input.get(pos) == Some(&'+') {
+///             let (rhs, next) = term(pos + 1);
+///             value += rhs;
+///             pos = next;
+///         }
+///         (value, pos)
+///     };
+///     let term = |pos: usize| -> (i64, usize) {
+///         let (mut value, mut pos) = factor(pos);
+///         while input.get(pos) == Some(&'*') {
+///             let (rhs, next) = factor(pos + 1);
+///             value *= rhs;
+///             pos = next;
+///         }
+///         (value, pos)
+///     };
+///     let factor = |pos: usize| -> (i64, usize) {
+///         if input[pos] == '(' {
+///             let (value, pos) = expr(pos + 1);
+///             (value, pos + 1)
+///         } else {
+///             (input[pos].to_digit(10).unwrap() as i64, pos + 1)
+///         }
+///     };
+/// }
+///
+/// assert_eq!(expr(0), (15, input.len()));
+/// ```
+///
+/// With patterns as parameters:
+///
+/// ```
+/// use fix_fn::fix_fns;
+///
+/// fix_fns! {
+///     let digits = |mut acc: Vec<u32>, n: u32| -> Vec<u32> {
+///         acc.push(n % 10);
+///         if n < 10 { acc } else { shift(acc, (n / 10, 1)) }
+///     };
+///     let shift = |acc: Vec<u32>, (n, _depth): (u32, u32)| -> Vec<u32> {
+///         digits(acc, n)
+///     };
+/// }
+///
+/// assert_eq!(digits(Vec::new(), 1234), [4, 3, 2, 1]);
+/// ```
+#[macro_export]
+macro_rules! fix_fns {
+    (
+        $(
+            let $name:ident = $($mov:ident)? |$($arg_name:ident : $arg_type:ty),* $(,)?|
+                -> $ret_type:ty
+            $body:block;
+        )+
+    ) => {
+        $crate::__fix_fns! {
+            [$( $name ($($arg_name : $arg_type),*) -> $ret_type; )+]
+            $( $name ($($mov)?) ($($arg_name : $arg_type),*) -> $ret_type $body )+
+        }
+    };
+    ($($rest:tt)*) => {
+        $crate::__fix_fns!(@normalize [] $($rest)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fns {
+    (
+        $all:tt
+        $( $name:ident ($($mov:ident)?) ($($arg_name:ident : $arg_type:ty),*) -> $ret_type:ty $body:block )+
+    ) => {
+        let fns = {
+            trait HideFns {
+                $(
+                    fn $name(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+                )+
+            }
+
+            #[allow(non_camel_case_types)]
+            struct HideFnsImpl<$($name: Fn(&dyn HideFns, $($arg_type ,)*) -> $ret_type ,)+> {
+                $($name: $name ,)+
+            }
+
+            #[allow(non_camel_case_types)]
+            impl<$($name: Fn(&dyn HideFns, $($arg_type ,)*) -> $ret_type ,)+> HideFns for HideFnsImpl<$($name ,)+> {
+                $(
+                    #[inline]
+                    fn $name(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                        (self.$name)(self, $($arg_name ,)*)
+                    }
+                )+
+            }
+
+            // inherent methods, so the group can be called after the trait is out of scope
+            #[allow(non_camel_case_types)]
+            impl<$($name: Fn(&dyn HideFns, $($arg_type ,)*) -> $ret_type ,)+> HideFnsImpl<$($name ,)+> {
+                $(
+                    #[inline]
+                    fn $name(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                        HideFns::$name(self, $($arg_name ,)*)
+                    }
+                )+
+            }
+
+            HideFnsImpl {
+                $(
+                    $name: $($mov)? |fns: &dyn HideFns, $($arg_name : $arg_type ,)*| -> $ret_type {
+                        $crate::__fix_fns!(@bind fns $all);
+                        {
+                            $body
+                        }
+                    },
+                )+
+            }
+        };
+
+        $(
+            #[allow(unused_variables)]
+            let $name = |$($arg_name : $arg_type),*| -> $ret_type {
+                fns.$name($($arg_name),*)
+            };
+        )+
+    };
+
+    // rewrites closures with patterns as parameters one by one, see `__fix_fn_patterns!`
+    (@normalize [$($done:tt)*]) => {
+        $crate::__fix_fns!(@done $($done)*)
+    };
+    (@normalize $done:tt let $name:ident = $($rest:tt)*) => {
+        $crate::__fix_fns!(@split $done $name [] $($rest)*)
+    };
+    (@split $done:tt $name:ident [$($closure:tt)*] ; $($rest:tt)*) => {
+        $crate::__fix_fns!(@closure $done [$($rest)*] $name $($closure)*)
+    };
+    (@split $done:tt $name:ident [$($closure:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__fix_fns!(@split $done $name [$($closure)* $next] $($rest)*)
+    };
+    (@closure $done:tt $rest:tt $name:ident $($mov:ident)? | $($params:tt)*) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fns] [@normalized $done $rest $name] $($mov)? |fns, $($params)*)
+    };
+    (
+        @normalized [$($done:tt)*] [$($rest:tt)*] $name:ident
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)*| -> $ret_type:ty $body:block
+    ) => {
+        $crate::__fix_fns!(@normalize [$($done)* let $name = $($mov)? |$($arg_name : $arg_type),*| -> $ret_type $body;] $($rest)*)
+    };
+    (
+        @done
+        $(
+            let $name:ident = $($mov:ident)? |$($arg_name:ident : $arg_type:ty),*|
+                -> $ret_type:ty
+            $body:block;
+        )+
+    ) => {
+        $crate::__fix_fns! {
+            [$( $name ($($arg_name : $arg_type),*) -> $ret_type; )+]
+            $( $name ($($mov)?) ($($arg_name : $arg_type),*) -> $ret_type $body )+
+        }
+    };
+    (@bind $fns:ident [$( $name:ident ($($arg_name:ident : $arg_type:ty),*) -> $ret_type:ty; )+]) => {
+        $(
+            #[allow(unused_variables)]
+            let $name = |$($arg_name : $arg_type),*| -> $ret_type { $fns.$name($($arg_name),*) };
+        )+
+    };
+    (@$($rest:tt)*) => {
+        compile_error!("fix_fns expects closures of the form `let name = |arg: Type, ...| -> Type { ... };`!");
+    };
+}
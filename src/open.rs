@@ -0,0 +1,216 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::fix::Fix;
+use crate::layer::RecursionLayer;
+
+/// A step function whose recursive calls are not tied to itself yet.
+///
+/// Implemented by all closures that take a handle for recursive calls and a tuple of
+/// arguments, and by steps wrapped in [`RecursionLayer`]s.
+pub trait Step<Args, R> {
+    /// Runs one step. Recursive calls go to `recur`.
+    fn step(&self, recur: &dyn Fn(Args) -> R, args: Args) -> R;
+}
+
+impl<F, Args, R> Step<Args, R> for F
+where
+    F: Fn(&dyn Fn(Args) -> R, Args) -> R,
+{
+    #[inline]
+    fn step(&self, recur: &dyn Fn(Args) -> R, args: Args) -> R {
+        self(recur, args)
+    }
+}
+
+/// A [`Step`] wrapped in a [`RecursionLayer`], see [`Open::layer`].
+#[derive(Debug, Clone)]
+pub struct Layered<S, L> {
+    step: S,
+    layer: L,
+}
+
+impl<S, L, Args, R> Step<Args, R> for Layered<S, L>
+where
+    S: Step<Args, R>,
+    L: RecursionLayer<Args, R>,
+{
+    #[inline]
+    fn step(&self, recur: &dyn Fn(Args) -> R, args: Args) -> R {
+        self.layer.call(args, &|args| self.step.step(recur, args))
+    }
+}
+
+/// The step function of a recursive closure, as created by `fix_fn!(open, ..)`.
+///
+/// The step takes all parameters except the first as a tuple of type `Args` and returns
+/// `R`, like a [`Fix`]. It is not recursive yet: [`Open::layer`] wraps it in
+/// [`RecursionLayer`]s, which then run around every call, and [`Open::fix`] ties the
+/// knot by passing the result as the handle for recursive calls.
+///
+/// # Example
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use fix_fn::{fix_fn, Inspect, Memoize};
+/// use std::cell::RefCell;
+///
+/// let log = RefCell::new(Vec::new());
+/// let fib = fix_fn!(open, |fib, i: u32| -> u64 {
+///     if i <= 1 {
+///         i as u64
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// })
+/// .layer(Memoize::new())
+/// .layer(Inspect::new(|depth, &(i,): &(u32,)| log.borrow_mut().push((depth, i))))
+/// .fix();
+///
+/// assert_eq!(fib.call(4), 3);
+/// // the inspection sees the calls that are answered from the cache, too
+/// assert_eq!(*log.borrow(), [(1, 4), (2, 3), (3, 2), (4, 1), (4, 0), (3, 1), (2, 2)]);
+/// ```
+///
+/// A single step can be tested on its own by passing a stub for the recursive calls
+/// to [`Open::call_with`]:
+///
+/// ```
+/// use fix_fn::fix_fn;
+/// use std::cell::RefCell;
+///
+/// enum Expr {
+///     Num(i64),
+///     Add(usize, usize),
+///     Neg(usize),
+/// }
+///
+/// // -(1 + 2)
+/// let exprs = [Expr::Neg(1), Expr::Add(2, 3), Expr::Num(1), Expr::Num(2)];
+///
+/// let eval = fix_fn!(open, |eval, expr: usize| -> i64 {
+///     match exprs[expr] {
+///         Expr::Num(n) => n,
+///         Expr::Add(a, b) => eval(a) + eval(b),
+///         Expr::Neg(a) => -eval(a),
+///     }
+/// });
+///
+/// let sub_calls = RefCell::new(Vec::new());
+/// let stub = |expr| {
+///     sub_calls.borrow_mut().push(expr);
+///     10
+/// };
+///
+/// assert_eq!(eval.call_with(stub, 1), 20);
+/// assert_eq!(*sub_calls.borrow(), [2, 3]);
+/// assert_eq!(eval.call_with(stub, 0), -10);
+///
+/// // and the whole recursion
+/// assert_eq!(eval.fix().call(0), -3);
+/// ```
+pub struct Open<S, Args, R> {
+    step: S,
+    marker: PhantomData<fn(Args) -> R>,
+}
+
+impl<F, Args, R> Open<F, Args, R>
+where
+    F: Fn(&dyn Fn(Args) -> R, Args) -> R,
+{
+    /// Creates an open recursive closure from `f`, which gets a handle for recursive
+    /// calls as first argument.
+    #[inline]
+    pub fn new(f: F) -> Self {
+        Open {
+            step: f,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S, Args, R> Open<S, Args, R>
+where
+    S: Step<Args, R>,
+{
+    /// Wraps the step in `layer`, so the layer runs around every call, recursive or not.
+    ///
+    /// Each layer wraps all layers that were added before it, so the last layer
+    /// sees a call first.
+    #[inline]
+    pub fn layer<L>(self, layer: L) -> Open<Layered<S, L>, Args, R>
+    where
+        L: RecursionLayer<Args, R>,
+    {
+        Open {
+            step: Layered {
+                step: self.step,
+                layer,
+            },
+            marker: PhantomData,
+        }
+    }
+
+    /// Ties the knot and returns the recursive closure.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn fix(self) -> Fix<impl Fn(&dyn Fn(Args) -> R, Args) -> R, Args, R> {
+        Fix::new(move |recur: &dyn Fn(Args) -> R, args| self.step.step(recur, args))
+    }
+
+    /// Runs a single step with a tuple of all arguments and passes recursive calls
+    /// to `stub` instead of the step itself. The layers run around this call only.
+    #[inline]
+    pub fn call_tuple_with(&self, stub: &dyn Fn(Args) -> R, args: Args) -> R {
+        self.step.step(stub, args)
+    }
+
+    /// Returns the step, including all layers.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.step
+    }
+}
+
+macro_rules! impl_open_call {
+    ($(($($arg_name:ident : $arg_type:ident),*))*) => {$(
+        impl<S, $($arg_type ,)* R> Open<S, ($($arg_type ,)*), R>
+        where
+            S: Step<($($arg_type ,)*), R>,
+        {
+            /// Runs a single step and passes recursive calls to `stub` instead of the step
+            /// itself, e.g. to test one level of the recursion in isolation.
+            #[inline]
+            #[allow(clippy::too_many_arguments)]
+            pub fn call_with(&self, stub: impl Fn($($arg_type),*) -> R, $($arg_name : $arg_type),*) -> R {
+                self.call_tuple_with(&|($($arg_name ,)*)| stub($($arg_name),*), ($($arg_name ,)*))
+            }
+        }
+    )*};
+}
+
+impl_open_call! {
+    ()
+    (a: A)
+    (a: A, b: B)
+    (a: A, b: B, c: C)
+    (a: A, b: B, c: C, d: D)
+    (a: A, b: B, c: C, d: D, e: E)
+    (a: A, b: B, c: C, d: D, e: E, g: G)
+}
+
+impl<S: Clone, Args, R> Clone for Open<S, Args, R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Open {
+            step: self.step.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S, Args, R> fmt::Debug for Open<S, Args, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Open").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,248 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::panic;
+use std::thread;
+
+/// Limits how many threads [`ThreadBudget::join`] may spawn at the same time.
+///
+/// Meant to be captured by a [`fix_fn_par!`](crate::fix_fn_par!) closure, so all
+/// recursive calls share the budget. Once it is used up, `join` runs both closures
+/// sequentially on the current thread until a spawned thread finishes.
+///
+/// Spawning a thread costs far more than a small recursive call. With a cutoff set by
+/// [`ThreadBudget::sequential_below`], [`ThreadBudget::join_sized`] runs subproblems
+/// below that size sequentially without touching the budget.
+#[derive(Debug)]
+pub struct ThreadBudget {
+    available: AtomicUsize,
+    cutoff: usize,
+}
+
+impl ThreadBudget {
+    /// Creates a budget that allows up to `threads` additional threads at the same time.
+    /// With `0`, everything runs on the calling thread.
+    pub fn new(threads: usize) -> Self {
+        ThreadBudget {
+            available: AtomicUsize::new(threads),
+            cutoff: 0,
+        }
+    }
+
+    /// Makes [`ThreadBudget::join_sized`] run both closures sequentially if the size of
+    /// the subproblem is less than `cutoff`.
+    pub fn sequential_below(mut self, cutoff: usize) -> Self {
+        self.cutoff = cutoff;
+        self
+    }
+
+    /// Creates a budget that keeps all available cores busy, as reported by
+    /// [`std::thread::available_parallelism`].
+    pub fn available_parallelism() -> Self {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        ThreadBudget::new(cores - 1)
+    }
+
+    /// Number of threads that may currently still be spawned.
+    pub fn available(&self) -> usize {
+        self.available.load(Ordering::Relaxed)
+    }
+
+    /// Runs `a` and `b`, in parallel if the budget allows, and returns both results.
+    ///
+    /// `b` runs on a new scoped thread if one is available, `a` always runs on the
+    /// current thread. A panic in either closure is propagated once both are done.
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB + Send,
+        RB: Send,
+    {
+        if !self.reserve() {
+            return (a(), b());
+        }
+
+        struct Release<'a>(&'a AtomicUsize);
+
+        impl Drop for Release<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Release);
+            }
+        }
+
+        thread::scope(|scope| {
+            let release = Release(&self.available);
+            let b = scope.spawn(move || {
+                let _release = release;
+                b()
+            });
+            let a = a();
+            match b.join() {
+                Ok(b) => (a, b),
+                Err(panic) => panic::resume_unwind(panic),
+            }
+        })
+    }
+
+    /// Like [`ThreadBudget::join`], but runs both closures sequentially if `size` is less
+    /// than the cutoff set by [`ThreadBudget::sequential_below`]. `size` is any measure of
+    /// the work `a` and `b` do together, e.g. the length of the slice they split.
+    pub fn join_sized<A, B, RA, RB>(&self, size: usize, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB + Send,
+        RB: Send,
+    {
+        if size < self.cutoff {
+            (a(), b())
+        } else {
+            self.join(a, b)
+        }
+    }
+
+    fn reserve(&self) -> bool {
+        self.available
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |available| {
+                available.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
+
+impl Default for ThreadBudget {
+    fn default() -> Self {
+        ThreadBudget::available_parallelism()
+    }
+}
+
+/// Like [`fix_fn!`](crate::fix_fn!), but the self handle can be shared with other threads.
+///
+/// The self handle is [`Copy`], [`Send`] and [`Sync`], so it can be moved into
+/// [`std::thread::scope`] workers that recurse further. This requires the closure to be
+/// [`Sync`], i.e. everything it captures has to be [`Sync`]. The resulting closure is
+/// [`Sync`] as well, and [`Send`] if the captures are.
+///
+/// [`ThreadBudget::join`] runs two recursive calls in parallel while there are threads
+/// left in the budget and sequentially otherwise. Capture one budget in the closure, so
+/// the whole recursion shares it. To keep small subproblems on the current thread,
+/// see [`ThreadBudget::join_sized`].
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::{fix_fn_par, ThreadBudget};
+///
+/// // subproblems of fewer than 4096 elements aren't worth a thread
+/// let budget = ThreadBudget::new(3).sequential_below(4096);
+/// let sort = fix_fn_par!(|sort, v: Vec<u32>| -> Vec<u32> {
+///     if v.len() <= 32 {
+///         let mut v = v;
+///         v.sort_unstable();
+///         return v;
+///     }
+///
+///     let (left, right) = v.split_at(v.len() / 2);
+///     let (left, right) =
+///         budget.join_sized(v.len(), || sort(left.to_vec()), || sort(right.to_vec()));
+///
+///     let mut result = Vec::with_capacity(v.len());
+///     let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+///     while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+///         result.push(if l <= r { left.next() } else { right.next() }.unwrap());
+///     }
+///     result.extend(left.chain(right));
+///     result
+/// });
+///
+/// let v: Vec<u32> = (0..100_000).map(|i| i * 7_919 % 100_003).collect();
+/// let mut expected = v.clone();
+/// expected.sort();
+/// assert_eq!(sort(v), expected);
+///
+/// // a tree reduction, using all cores
+/// struct Tree {
+///     value: u64,
+///     children: Vec<Tree>,
+/// }
+///
+/// let budget = ThreadBudget::default();
+/// let sum = fix_fn_par!(|sum, trees: &[Tree]| -> u64 {
+///     match trees {
+///         [] => 0,
+///         [tree] => tree.value + sum(&tree.children),
+///         _ => {
+///             let (left, right) = trees.split_at(trees.len() / 2);
+///             let (left, right) = budget.join(|| sum(left), || sum(right));
+///             left + right
+///         }
+///     }
+/// });
+///
+/// let tree = Tree {
+///     value: 1,
+///     children: (0..10).map(|value| Tree { value, children: vec![] }).collect(),
+/// };
+/// assert_eq!(sum(std::slice::from_ref(&tree)), 46);
+/// ```
+#[macro_export]
+macro_rules! fix_fn_par {
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn: ::core::marker::Sync {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type + ::core::marker::Sync>(F);
+
+        impl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type + ::core::marker::Sync> HideFn for HideFnImpl<F> {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                self.0(self, $($arg_name ,)*)
+            }
+        }
+
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = move |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            }
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            inner.call($($arg_name),*)
+        }
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_par needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn_par] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,54 @@
+/// Rewrites a closure definition whose parameters are patterns into one whose
+/// parameters are plain identifiers, destructuring the patterns at the start of the
+/// body, and passes it on to `$callback!($($prefix)* ..)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_patterns {
+    (
+        [$($callback:tt)*] [$($prefix:tt)*]
+        $($mov:ident)? |$self_arg:ident , $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!(@param [$($callback)*] [$($prefix)*] [$($mov)?] $self_arg [] [] $($rest)*)
+    };
+
+    // `arg` gets a new hygienic context in every expansion, so it is unique for each parameter
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident [$($args:tt)*] [$($pat:tt)+] : $arg_type:ty , $($rest:tt)*) => {
+        $crate::__fix_fn_patterns!(@param $callback $prefix $mov $self_arg [$($args)* (arg : $arg_type = $($pat)+)] [] $($rest)*)
+    };
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident [$($args:tt)*] [$($pat:tt)+] : $arg_type:ty | $($rest:tt)*) => {
+        $crate::__fix_fn_patterns!(@end $callback $prefix $mov $self_arg [$($args)* (arg : $arg_type = $($pat)+)] $($rest)*)
+    };
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident [$($args:tt)*] [] | $($rest:tt)*) => {
+        $crate::__fix_fn_patterns!(@end $callback $prefix $mov $self_arg [$($args)*] $($rest)*)
+    };
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident $args:tt [$($pat:tt)*] , $($rest:tt)*) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident $args:tt [$($pat:tt)*] | $($rest:tt)*) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (@param $callback:tt $prefix:tt $mov:tt $self_arg:ident $args:tt [$($pat:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__fix_fn_patterns!(@param $callback $prefix $mov $self_arg $args [$($pat)* $next] $($rest)*)
+    };
+    (@param $($rest:tt)*) => {
+        compile_error!("Missing `|` after the parameters of the closure!");
+    };
+
+    (
+        @end [$($callback:tt)*] [$($prefix:tt)*] [$($mov:ident)?] $self_arg:ident
+        [$(($arg_name:ident : $arg_type:ty = $($pat:tt)+))*]
+        -> $ret_type:ty
+        $body:block
+    ) => {
+        $($callback)*! {
+            $($prefix)*
+            $($mov)? |$self_arg $(, $arg_name : $arg_type)*| -> $ret_type {
+                $(let $($pat)+ = $arg_name;)*
+                $body
+            }
+        }
+    };
+    (@end $($rest:tt)*) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+}
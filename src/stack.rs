@@ -0,0 +1,90 @@
+use std::panic;
+use std::thread;
+
+/// Runs `f` on a new thread with a stack of `size` bytes and returns its result.
+///
+/// The current thread blocks until `f` is done. If `f` panics, the panic is resumed on
+/// the current thread. This is what `fix_fn!(stack_size = .., ..)` does for every
+/// top-level call, but it can also wrap any other deeply recursive code.
+///
+/// # Panics
+///
+/// Panics if the thread cannot be spawned, e.g. because `size` bytes cannot be
+/// allocated.
+///
+/// # Example
+///
+/// ```
+/// fn depth(n: u64) -> u64 {
+///     if n == 0 { 0 } else { depth(n - 1) + 1 }
+/// }
+///
+/// assert_eq!(fix_fn::with_stack_size(64 << 20, || depth(100_000)), 100_000);
+/// ```
+pub fn with_stack_size<R, F>(size: usize, f: F) -> R
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    thread::scope(|scope| {
+        let thread = thread::Builder::new()
+            .stack_size(size)
+            .spawn_scoped(scope, f)
+            .expect("failed to spawn thread");
+        match thread.join() {
+            Ok(result) => result,
+            Err(panic) => panic::resume_unwind(panic),
+        }
+    })
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_stack_size {
+    (
+        [$size:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        let size: usize = $size;
+        let inner = $crate::fix_fn!($($mov)? |$self_arg $(, $arg_name : $arg_type)*| -> $ret_type $body);
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            $crate::with_stack_size(size, || inner($($arg_name),*))
+        }
+    }};
+    (
+        [$size:expr]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        [$size:expr]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$size:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$size:expr]
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_stack_size] [[$size]] $($mov)? |$self_arg, $($rest)*)
+    };
+    ([$size:expr] $($rest:tt)*) => {
+        compile_error!("fix_fn!(stack_size = .., ..) can't be combined with other modes and expects a closure of the form `|f, arg: Type, ..| -> R { .. }`!");
+    };
+}
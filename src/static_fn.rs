@@ -0,0 +1,47 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_static {
+    (move $($rest:tt)*) => {
+        compile_error!("fix_fn!(static, ..) can't capture its environment, so it doesn't accept `move`!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        #[inline]
+        fn fix_fn_static($($arg_name : $arg_type),*) -> $ret_type {
+            let $self_arg = fix_fn_static;
+            {
+                $body
+            }
+        }
+
+        fix_fn_static
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_static] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,291 @@
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::convert::Infallible;
+use core::fmt;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::guard::Guard;
+
+/// Call statistics of a [`fix_fn_stats!`](crate::fix_fn_stats!) closure.
+///
+/// The statistics are updated while the closure runs, so they can also be read inside
+/// the body. The top-level call has depth 1.
+pub struct Stats {
+    calls: Cell<usize>,
+    depth: Cell<usize>,
+    max_depth: Cell<usize>,
+    calls_per_depth: RefCell<Vec<usize>>,
+    #[cfg(feature = "std")]
+    timer: Option<Timer>,
+}
+
+#[cfg(feature = "std")]
+struct Timer {
+    total: Cell<Duration>,
+    started: Cell<Option<Instant>>,
+}
+
+impl Stats {
+    #[doc(hidden)]
+    pub fn new() -> Self {
+        Stats {
+            calls: Cell::new(0),
+            depth: Cell::new(0),
+            max_depth: Cell::new(0),
+            calls_per_depth: RefCell::new(Vec::new()),
+            #[cfg(feature = "std")]
+            timer: None,
+        }
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "std")]
+    pub fn timed() -> Self {
+        Stats {
+            timer: Some(Timer {
+                total: Cell::new(Duration::ZERO),
+                started: Cell::new(None),
+            }),
+            ..Stats::new()
+        }
+    }
+
+    /// Total number of calls, recursive or not.
+    pub fn calls(&self) -> usize {
+        self.calls.get()
+    }
+
+    /// Deepest nesting that was reached.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth.get()
+    }
+
+    /// Number of calls at `depth`.
+    pub fn calls_at_depth(&self, depth: usize) -> usize {
+        match depth.checked_sub(1) {
+            Some(index) => self.calls_per_depth.borrow().get(index).copied().unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Number of calls at each depth, starting with the top-level calls at depth 1.
+    pub fn calls_per_depth(&self) -> Vec<usize> {
+        self.calls_per_depth.borrow().clone()
+    }
+
+    /// Wall time spent in top-level calls, if the closure was created with
+    /// `fix_fn_stats!(timed, ..)`. A call that is still running is not included.
+    pub fn time(&self) -> Option<Duration> {
+        #[cfg(feature = "std")]
+        return self.timer.as_ref().map(|timer| timer.total.get());
+        #[cfg(not(feature = "std"))]
+        None
+    }
+
+    /// Sets all statistics back to zero. Calls that are running while the statistics
+    /// are reset are only counted at the depths they haven't reached yet.
+    pub fn reset(&self) {
+        self.calls.set(0);
+        self.max_depth.set(0);
+        self.calls_per_depth.borrow_mut().clear();
+        #[cfg(feature = "std")]
+        if let Some(timer) = &self.timer {
+            timer.total.set(Duration::ZERO);
+        }
+    }
+}
+
+impl fmt::Debug for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stats")
+            .field("calls", &self.calls())
+            .field("max_depth", &self.max_depth())
+            .field("calls_per_depth", &self.calls_per_depth.borrow())
+            .field("time", &self.time())
+            .finish()
+    }
+}
+
+impl Guard for Stats {
+    type Error = Infallible;
+
+    #[inline]
+    fn enter(&self) -> Result<(), Infallible> {
+        let depth = self.depth.get() + 1;
+        self.depth.set(depth);
+        self.calls.set(self.calls.get() + 1);
+        self.max_depth.set(self.max_depth.get().max(depth));
+
+        let mut calls_per_depth = self.calls_per_depth.borrow_mut();
+        if calls_per_depth.len() < depth {
+            calls_per_depth.resize(depth, 0);
+        }
+        calls_per_depth[depth - 1] += 1;
+
+        #[cfg(feature = "std")]
+        if let (Some(timer), 1) = (&self.timer, depth) {
+            timer.started.set(Some(Instant::now()));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn exit(&self) {
+        let depth = self.depth.get() - 1;
+        self.depth.set(depth);
+
+        #[cfg(feature = "std")]
+        if let (Some(timer), 0) = (&self.timer, depth) {
+            if let Some(started) = timer.started.take() {
+                timer.total.set(timer.total.get() + started.elapsed());
+            }
+        }
+    }
+}
+
+/// Like [`fix_fn!`](crate::fix_fn!), but also records call statistics.
+///
+/// Returns a tuple of the recursive closure and an [`Rc`](alloc::rc::Rc) to its
+/// [`Stats`]: the total number of calls, the maximal depth and the number of calls
+/// at each depth. `fix_fn_stats!(timed, |..| -> R { .. })` additionally measures the
+/// wall time spent in the closure.
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `alloc` feature,
+/// timing requires the `std` feature.
+///
+/// # Example
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use fix_fn::fix_fn_stats;
+///
+/// let (fib, stats) = fix_fn_stats!(|fib, i: u32| -> u64 {
+///     if i <= 1 {
+///         i as u64
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// });
+///
+/// assert_eq!(fib(10), 55);
+/// assert_eq!(stats.calls(), 177);
+/// assert_eq!(stats.max_depth(), 10);
+/// assert_eq!(stats.calls_per_depth()[..4], [1, 2, 4, 8]);
+/// assert_eq!(stats.calls_at_depth(10), 2);
+/// assert_eq!(stats.time(), None);
+///
+/// stats.reset();
+/// assert_eq!(fib(1), 1);
+/// assert_eq!(stats.calls(), 1);
+///
+/// let (fib, stats) = fix_fn_stats!(timed, |fib, i: u32| -> u64 {
+///     if i <= 1 { i as u64 } else { fib(i - 1) + fib(i - 2) }
+/// });
+///
+/// fib(20);
+/// assert!(stats.time().is_some());
+/// ```
+#[macro_export]
+macro_rules! fix_fn_stats {
+    (timed, $($rest:tt)*) => {
+        $crate::__fix_fn_stats_timed!($($rest)*)
+    };
+    ($($rest:tt)*) => {
+        $crate::__fix_fn_stats!([$crate::Stats::new()] $($rest)*)
+    };
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_stats_timed {
+    ($($rest:tt)*) => {
+        $crate::__fix_fn_stats!([$crate::Stats::timed()] $($rest)*)
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_stats_timed {
+    ($($rest:tt)*) => {
+        compile_error!("fix_fn_stats!(timed, ..) requires the `std` feature of fix_fn!");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fix_fn_stats {
+    (
+        [$stats:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type>(
+            F,
+            $crate::__private::Rc<$crate::Stats>,
+        );
+
+        impl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type> HideFn for HideFnImpl<F> {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                $crate::__private::observed(&*self.1, || self.0(self, $($arg_name ,)*))
+            }
+        }
+
+        let stats = $crate::__private::Rc::new($stats);
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            },
+            $crate::__private::Rc::clone(&stats),
+        );
+
+        (
+            move |$($arg_name : $arg_type),*| -> $ret_type {
+                inner.call($($arg_name),*)
+            },
+            stats,
+        )
+    }};
+    (
+        [$stats:expr]
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_stats needs return type!");
+    };
+    (
+        [$stats:expr]
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        [$stats:expr]
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        [$stats:expr] $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::__fix_fn_stats] [[$stats]] $($mov)? |$self_arg, $($rest)*)
+    };
+}
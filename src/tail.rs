@@ -0,0 +1,138 @@
+/// The outcome of one step of a closure created with [`fix_fn_tail!`](crate::fix_fn_tail!).
+///
+/// Calling the self handle inside the body produces [`Tail::Recur`] with the new
+/// arguments. Any result can be turned into [`Tail::Done`] with [`From`]/[`Into`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[must_use = "a self call only recurses when its `Tail` is returned from the body"]
+pub enum Tail<A, R> {
+    /// Run the body again with these arguments.
+    Recur(A),
+    /// Stop and return this value.
+    Done(R),
+}
+
+impl<A, R> From<R> for Tail<A, R> {
+    #[inline]
+    fn from(result: R) -> Self {
+        Tail::Done(result)
+    }
+}
+
+/// Like [`fix_fn!`](crate::fix_fn!), but for tail recursion that runs in constant stack space.
+///
+/// The body returns a [`Tail`] instead of the declared result type: calling the self
+/// handle does not recurse, but returns [`Tail::Recur`] with the new arguments, while
+/// [`Tail::Done`] (or `result.into()`) finishes with a result. The resulting closure
+/// runs the body in a loop until it is done, so the recursion depth is not limited by
+/// the stack.
+///
+/// This also means that calls of the self handle must be in tail position. A self call
+/// only builds a [`Tail::Recur`], which has to be returned from the body to recurse.
+/// Its result can't be used as the declared result type, and a self call whose `Tail`
+/// is dropped doesn't run at all.
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_tail;
+///
+/// let sum = fix_fn_tail!(|sum, n: u64, acc: u64| -> u64 {
+///     if n == 0 {
+///         acc.into()
+///     } else {
+///         // does not grow the stack
+///         sum(n - 1, acc + n)
+///     }
+/// });
+///
+/// assert_eq!(sum(1_000_000, 0), 500_000_500_000);
+/// ```
+///
+/// Walking a long linked list:
+///
+/// ```
+/// use fix_fn::{fix_fn_tail, Tail};
+///
+/// struct Node {
+///     value: u32,
+///     next: Option<Box<Node>>,
+/// }
+///
+/// let mut list = None;
+/// for value in 0..500_000 {
+///     list = Some(Box::new(Node { value, next: list }));
+/// }
+///
+/// let find = fix_fn_tail!(|find, node: Option<&Node>, value: u32| -> bool {
+///     match node {
+///         None => Tail::Done(false),
+///         Some(node) if node.value == value => Tail::Done(true),
+///         Some(node) => find(node.next.as_deref(), value),
+///     }
+/// });
+///
+/// assert!(find(list.as_deref(), 0));
+/// assert!(!find(list.as_deref(), 500_000));
+/// # // drop the list iteratively, so the test itself does not overflow
+/// # while let Some(node) = list { list = node.next; }
+/// ```
+#[macro_export]
+macro_rules! fix_fn_tail {
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        struct TailFnImpl<F: Fn($($arg_type ,)*) -> $crate::Tail<($($arg_type ,)*), $ret_type>>(F);
+
+        let TailFnImpl(body) = TailFnImpl(
+            #[inline]
+            $($mov)?
+            |$($arg_name : $arg_type ,)*| {
+                let TailFnImpl($self_arg) = TailFnImpl(|$($arg_name ,)*| $crate::Tail::Recur(($($arg_name ,)*)));
+                {
+                    $body
+                }
+            }
+        );
+
+        #[inline]
+        move |$($arg_name : $arg_type),*| -> $ret_type {
+            let mut args = ($($arg_name ,)*);
+            loop {
+                let ($($arg_name ,)*) = args;
+                match body($($arg_name ,)*) {
+                    $crate::Tail::Recur(next) => args = next,
+                    $crate::Tail::Done(result) => return result,
+                }
+            }
+        }
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_tail needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn_tail] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}
@@ -0,0 +1,365 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Ref, RefCell};
+use core::fmt::{self, Debug, Write};
+
+use crate::layer::RecursionLayer;
+
+/// One call in a [`CallTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallNode {
+    /// The [`Debug`] representations of all arguments, separated by `", "`.
+    pub args: String,
+    /// The [`Debug`] representation of the result, or `None` if the call has not
+    /// returned (yet), e.g. because it panicked.
+    pub result: Option<String>,
+    /// Index of the calling node, `None` for the top-level call.
+    pub parent: Option<usize>,
+    /// Indices of the nodes called by this one, in call order.
+    pub children: Vec<usize>,
+}
+
+/// Call tree of the last top-level call of a [`fix_fn_trace!`](crate::fix_fn_trace!)
+/// closure, or of an [`Open`](crate::Open) closure that has the tree as a layer.
+///
+/// The nodes are stored in call order, so the top-level call is node `0` and every
+/// node comes after its parent.
+#[derive(Debug)]
+pub struct CallTree {
+    name: &'static str,
+    nodes: RefCell<Vec<CallNode>>,
+    stack: RefCell<Vec<usize>>,
+}
+
+impl CallTree {
+    /// Creates an empty call tree, e.g. to use it as a [`RecursionLayer`]. `name` is
+    /// the name of the function in the rendered tree.
+    pub fn new(name: &'static str) -> Self {
+        CallTree {
+            name,
+            nodes: RefCell::new(Vec::new()),
+            stack: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// All recorded calls, in call order.
+    pub fn nodes(&self) -> Ref<'_, [CallNode]> {
+        Ref::map(self.nodes.borrow(), Vec::as_slice)
+    }
+
+    /// Number of recorded calls.
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    /// Returns `true` if no call was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    /// Renders the tree in the Graphviz DOT language.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n");
+        for (id, node) in self.nodes().iter().enumerate() {
+            let label = self.label(node);
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            dot += &format!("    n{} [label=\"{}\"];\n", id, label);
+            if let Some(parent) = node.parent {
+                dot += &format!("    n{} -> n{};\n", parent, id);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// Renders the tree as indented text, one call per line.
+    pub fn to_ascii(&self) -> String {
+        let nodes = self.nodes();
+        let mut ascii = String::new();
+        // nodes to render with the prefix of their line and of the lines of their children
+        let mut pending = Vec::new();
+        if !nodes.is_empty() {
+            pending.push((0, String::new(), String::new()));
+        }
+        while let Some((id, prefix, child_prefix)) = pending.pop() {
+            let node = &nodes[id];
+            ascii += &prefix;
+            ascii += &self.label(node);
+            ascii.push('\n');
+
+            for (i, &child) in node.children.iter().enumerate().rev() {
+                let (prefix, nested) = if i + 1 == node.children.len() {
+                    ("`-- ", "    ")
+                } else {
+                    ("|-- ", "|   ")
+                };
+                pending.push((child, child_prefix.clone() + prefix, child_prefix.clone() + nested));
+            }
+        }
+        ascii
+    }
+
+    /// Renders the tree as JSON lines, one object per call in call order.
+    ///
+    /// Every object has the fields `id`, `parent`, `depth`, `args` and `result`. The
+    /// top-level call has depth 1 and no parent.
+    pub fn to_json_lines(&self) -> String {
+        let nodes = self.nodes();
+        let mut depths = Vec::with_capacity(nodes.len());
+        let mut json = String::new();
+        for (id, node) in nodes.iter().enumerate() {
+            let depth = node.parent.map_or(1, |parent| depths[parent] + 1);
+            depths.push(depth);
+
+            json += &format!("{{\"id\":{},\"parent\":", id);
+            match node.parent {
+                Some(parent) => json += &format!("{}", parent),
+                None => json += "null",
+            }
+            json += &format!(",\"depth\":{},\"args\":{},\"result\":", depth, JsonString(&node.args));
+            match &node.result {
+                Some(result) => json += &format!("{}", JsonString(result)),
+                None => json += "null",
+            }
+            json += "}\n";
+        }
+        json
+    }
+
+    fn label(&self, node: &CallNode) -> String {
+        match &node.result {
+            Some(result) => format!("{}({}) = {}", self.name, node.args, result),
+            None => format!("{}({})", self.name, node.args),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn enter(&self, args: String) -> Recording<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        let mut stack = self.stack.borrow_mut();
+        // a new top-level call starts a new tree
+        if stack.is_empty() {
+            nodes.clear();
+        }
+        let id = nodes.len();
+        let parent = stack.last().copied();
+        if let Some(parent) = parent {
+            nodes[parent].children.push(id);
+        }
+        nodes.push(CallNode {
+            args,
+            result: None,
+            parent,
+            children: Vec::new(),
+        });
+        stack.push(id);
+        Recording { tree: self, id }
+    }
+}
+
+/// A call that is being recorded in a [`CallTree`].
+#[doc(hidden)]
+pub struct Recording<'a> {
+    tree: &'a CallTree,
+    id: usize,
+}
+
+impl Recording<'_> {
+    pub fn finish<R: Debug>(self, result: &R) {
+        self.tree.nodes.borrow_mut()[self.id].result = Some(format!("{:?}", result));
+    }
+}
+
+impl Drop for Recording<'_> {
+    fn drop(&mut self) {
+        self.tree.stack.borrow_mut().pop();
+    }
+}
+
+impl<Args: DebugArgs, R: Debug> RecursionLayer<Args, R> for CallTree {
+    fn call(&self, args: Args, next: &dyn Fn(Args) -> R) -> R {
+        let recording = self.enter(args.debug_args());
+        let result = next(args);
+        recording.finish(&result);
+        result
+    }
+}
+
+/// Tuples of arguments that can be recorded by a [`CallTree`] used as a
+/// [`RecursionLayer`].
+pub trait DebugArgs {
+    /// The [`Debug`] representations of all elements, separated by `", "`.
+    fn debug_args(&self) -> String;
+}
+
+#[doc(hidden)]
+pub fn debug_args(args: &[&dyn Debug]) -> String {
+    let mut formatted = String::new();
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            formatted += ", ";
+        }
+        let _ = write!(formatted, "{:?}", arg);
+    }
+    formatted
+}
+
+macro_rules! impl_debug_args {
+    ($(($($index:tt : $arg_type:ident),*))*) => {$(
+        impl<$($arg_type: Debug),*> DebugArgs for ($($arg_type ,)*) {
+            fn debug_args(&self) -> String {
+                debug_args(&[$(&self.$index),*])
+            }
+        }
+    )*};
+}
+
+impl_debug_args! {
+    ()
+    (0: A)
+    (0: A, 1: B)
+    (0: A, 1: B, 2: C)
+    (0: A, 1: B, 2: C, 3: D)
+    (0: A, 1: B, 2: C, 3: D, 4: E)
+    (0: A, 1: B, 2: C, 3: D, 4: E, 5: G)
+}
+
+struct JsonString<'a>(&'a str);
+
+impl fmt::Display for JsonString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Like [`fix_fn!`](crate::fix_fn!), but records the call tree of the last top-level call.
+///
+/// Returns a tuple of the recursive closure and an [`Rc`](alloc::rc::Rc) to its
+/// [`CallTree`]. Every call stores the [`Debug`] representations of its arguments and
+/// its result, so all parameter types except the first and the result type must
+/// implement [`Debug`]. The tree can be rendered as Graphviz DOT, as an indented
+/// ASCII tree or as JSON lines.
+///
+/// Every call is recorded, so the tree grows as large as the recursion. This is meant
+/// for debugging and teaching, e.g. to spot repeated subcalls that could be memoized.
+///
+/// All other rules of [`fix_fn!`](crate::fix_fn!) apply. Requires the `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// use fix_fn::fix_fn_trace;
+///
+/// let (fib, calls) = fix_fn_trace!(|fib, i: u32| -> u32 {
+///     if i <= 1 {
+///         i
+///     } else {
+///         fib(i - 1) + fib(i - 2)
+///     }
+/// });
+///
+/// assert_eq!(fib(3), 2);
+/// assert_eq!(calls.len(), 5);
+/// assert_eq!(calls.to_ascii(), "\
+/// fib(3) = 2
+/// |-- fib(2) = 1
+/// |   |-- fib(1) = 1
+/// |   `-- fib(0) = 0
+/// `-- fib(1) = 1
+/// ");
+///
+/// assert!(calls.to_dot().contains("n0 -> n4;"));
+/// assert_eq!(
+///     calls.to_json_lines().lines().nth(1),
+///     Some(r#"{"id":1,"parent":0,"depth":2,"args":"2","result":"1"}"#)
+/// );
+///
+/// // every top-level call records a new tree
+/// fib(1);
+/// assert_eq!(calls.to_ascii(), "fib(1) = 1\n");
+/// ```
+#[macro_export]
+macro_rules! fix_fn_trace {
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident : $arg_type:ty)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {{
+        trait HideFn {
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type;
+        }
+
+        struct HideFnImpl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type>(
+            F,
+            $crate::__private::Rc<$crate::CallTree>,
+        );
+
+        impl<F: Fn(&dyn HideFn, $($arg_type ,)*) -> $ret_type> HideFn for HideFnImpl<F> {
+            #[inline]
+            fn call(&self, $($arg_name : $arg_type ,)*) -> $ret_type {
+                let recording = self.1.enter($crate::__private::debug_args(&[$(&$arg_name as &dyn ::core::fmt::Debug),*]));
+                let result = self.0(self, $($arg_name ,)*);
+                recording.finish(&result);
+                result
+            }
+        }
+
+        let tree = $crate::__private::Rc::new($crate::CallTree::new(stringify!($self_arg)));
+        let inner = HideFnImpl(
+            #[inline]
+            $($mov)?
+            |$self_arg, $($arg_name : $arg_type ,)*| -> $ret_type {
+                let $self_arg = |$($arg_name : $arg_type ),*| $self_arg.call($($arg_name ,)*);
+                {
+                    $body
+                }
+            },
+            $crate::__private::Rc::clone(&tree),
+        );
+
+        (
+            move |$($arg_name : $arg_type),*| -> $ret_type {
+                inner.call($($arg_name),*)
+            },
+            tree,
+        )
+    }};
+    (
+        $($mov:ident)? |$($arg_name:ident $(: $arg_type:ty)?),* $(,)?|
+        $body:expr
+    ) => {
+        compile_error!("Closure passed to fix_fn_trace needs return type!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident : $self_type:ty $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!(concat!("First parameter ", stringify!($self_arg), " may not have type annotation!"));
+    };
+    (
+        $($mov:ident)? |$self_arg:ident $(, $arg_name:ident $(: $arg_type:ty)?)* $(,)? |
+            -> $ret_type:ty
+        $body:block
+    ) => {
+        compile_error!("All parameters except first need to have an explicit type annotation!");
+    };
+    (
+        $($mov:ident)? |$self_arg:ident, $($rest:tt)*
+    ) => {
+        $crate::__fix_fn_patterns!([$crate::fix_fn_trace] [] $($mov)? |$self_arg, $($rest)*)
+    };
+}